@@ -1,11 +1,20 @@
 use forests_primitives_core::ParaId;
-use kumandra_primitive::{AccountId, AuraId, Signature};
-use kumandra_runtime::EXISTENTIAL_DEPOSIT;
+use kumandra_primitive::{AccountId, AssetId, AuraId, Balance, BlockNumber, Signature};
+use kumandra_runtime::{
+	xcm_config::{KmdPerSecond, RelayAssetId},
+	EXISTENTIAL_DEPOSIT,
+};
+use pallet_asset_registry::GenesisAsset;
 use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup};
 use sc_service::ChainType;
 use serde::{Deserialize, Serialize};
 use sp_core::{sr25519, Pair, Public};
 use sp_runtime::traits::{IdentifyAccount, Verify};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::Path,
+};
+use xcm::latest::MultiLocation;
 
 /// Returns the properties for the [`KumandraChainSpec`].
 pub fn kumandra_chain_spec_properties() -> serde_json::map::Map<String, serde_json::Value> {
@@ -49,6 +58,74 @@ impl Extensions {
 	}
 }
 
+/// A vesting allocation of the genesis, as found in a vesting JSON file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct VestingEntry {
+	/// The vested account, in SS58 format.
+	pub account: AccountId,
+	/// The balance the account is endowed with, including the vested part.
+	pub balance: Balance,
+	/// The block at which the balance starts to unlock.
+	pub begin: BlockNumber,
+	/// The number of blocks over which the locked balance unlocks linearly.
+	pub length: BlockNumber,
+	/// The part of the balance that is not locked at genesis.
+	pub liquid: Balance,
+}
+
+/// Parse the vesting entries of a JSON file, which holds an array of [`VestingEntry`].
+///
+/// Entries are rejected when the genesis could not build their schedule: accounts must appear
+/// once, and the locked part of a balance must unlock at least one unit per block.
+pub fn vesting_from_json(json: &str) -> Result<Vec<VestingEntry>, String> {
+	let entries: Vec<VestingEntry> =
+		serde_json::from_str(json).map_err(|e| format!("Invalid vesting file: {}", e))?;
+
+	let mut accounts = BTreeSet::new();
+	for entry in &entries {
+		if !accounts.insert(&entry.account) {
+			return Err(format!("Invalid vesting file: {} is vested twice", entry.account))
+		}
+		let locked = entry.balance.checked_sub(entry.liquid).unwrap_or_default();
+		if locked == 0 {
+			return Err(format!(
+				"Invalid vesting file: the liquid part of {} is not less than its balance",
+				entry.account
+			))
+		}
+		if locked < Balance::from(entry.length) {
+			return Err(format!(
+				"Invalid vesting file: the locked part of {} unlocks less than one unit per block",
+				entry.account
+			))
+		}
+	}
+	Ok(entries)
+}
+
+/// The environment variable naming the vesting JSON file of the local testnet genesis.
+///
+/// The file is read when the chain spec is built. Without it, the bundled
+/// `res/local-vesting.json` is used.
+pub const VESTING_FILE_VAR: &str = "KUMANDRA_VESTING_FILE";
+
+/// Read the vesting entries of the JSON file at `path`, see [`vesting_from_json`].
+pub fn vesting_from_file(path: &Path) -> Result<Vec<VestingEntry>, String> {
+	let json = std::fs::read_to_string(path)
+		.map_err(|e| format!("Cannot read vesting file {}: {}", path.display(), e))?;
+	vesting_from_json(&json)
+}
+
+/// The vesting entries of the local testnet genesis, from the file named by
+/// [`VESTING_FILE_VAR`] or else from the bundled file.
+fn local_vesting() -> Result<Vec<VestingEntry>, String> {
+	match std::env::var_os(VESTING_FILE_VAR) {
+		Some(path) => vesting_from_file(Path::new(&path)),
+		None => vesting_from_json(include_str!("../res/local-vesting.json")),
+	}
+}
+
 type AccountPublic = <Signature as Verify>::Signer;
 
 /// Generate collator keys from seed.
@@ -108,6 +185,7 @@ pub fn development_config() -> ChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
 				2000.into(),
+				Vec::new(),
 			)
 		},
 		Vec::new(),
@@ -122,8 +200,10 @@ pub fn development_config() -> ChainSpec {
 	)
 }
 
-pub fn local_testnet_config() -> ChainSpec {
-	ChainSpec::from_genesis(
+pub fn local_testnet_config() -> Result<ChainSpec, String> {
+	let vesting = local_vesting()?;
+
+	Ok(ChainSpec::from_genesis(
 		// Name
 		"Local Testnet",
 		// ID
@@ -157,6 +237,7 @@ pub fn local_testnet_config() -> ChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
 				2000.into(),
+				vesting.clone(),
 			)
 		},
 		// Bootnodes
@@ -174,23 +255,62 @@ pub fn local_testnet_config() -> ChainSpec {
 			relay_chain: "selendra-local".into(), // You MUST set this to the correct network!
 			para_id: 2000,
 		},
-	)
+	))
+}
+
+/// The registration of the relay-chain token. Execution is priced the same in the relay-chain
+/// token as in KMD.
+fn relay_token_registration() -> GenesisAsset<AssetId> {
+	GenesisAsset {
+		asset_id: RelayAssetId::get(),
+		location: MultiLocation::parent(),
+		name: b"Selendra".to_vec(),
+		symbol: b"SEL".to_vec(),
+		decimals: 18,
+		existential_deposit: EXISTENTIAL_DEPOSIT,
+		units_per_second: Some(KmdPerSecond::get()),
+		is_sufficient: true,
+	}
 }
 
 fn testnet_genesis(
 	invulnerables: Vec<(AccountId, AuraId)>,
 	endowed_accounts: Vec<AccountId>,
 	id: ParaId,
+	vesting: Vec<VestingEntry>,
 ) -> kumandra_runtime::GenesisConfig {
+	// Vested accounts may also be endowed, their balances add up.
+	let mut balances: BTreeMap<AccountId, Balance> =
+		endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect();
+	for entry in &vesting {
+		let balance = balances.entry(entry.account.clone()).or_default();
+		*balance = balance.saturating_add(entry.balance);
+	}
+	// The vesting genesis locks all but the liquid part of the whole balance, so the endowment
+	// is counted as liquid to keep it unlocked.
+	let vesting = vesting
+		.into_iter()
+		.map(|entry| {
+			let locked = entry.balance - entry.liquid;
+			let liquid = balances[&entry.account] - locked;
+			(entry.account, entry.begin, entry.length, liquid)
+		})
+		.collect();
+
 	kumandra_runtime::GenesisConfig {
 		system: kumandra_runtime::SystemConfig {
 			code: kumandra_runtime::WASM_BINARY
 				.expect("WASM binary was not build, please build it!")
 				.to_vec(),
 		},
-		balances: kumandra_runtime::BalancesConfig {
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+		balances: kumandra_runtime::BalancesConfig { balances: balances.into_iter().collect() },
+		assets: Default::default(),
+		// The relay-chain token is a sufficient foreign asset. It is only minted and burned by XCM,
+		// against the reserve held on the relay chain.
+		asset_registry: kumandra_runtime::AssetRegistryConfig {
+			assets: vec![relay_token_registration()],
 		},
+		vesting: kumandra_runtime::VestingConfig { vesting },
 		parachain_info: kumandra_runtime::ParachainInfoConfig { parachain_id: id },
 		collator_selection: kumandra_runtime::CollatorSelectionConfig {
 			invulnerables: invulnerables.iter().cloned().map(|(acc, _)| acc).collect(),
@@ -217,5 +337,20 @@ fn testnet_genesis(
 		selendra_xcm: kumandra_runtime::SelendraXcmConfig {
 			safe_xcm_version: Some(SAFE_XCM_VERSION),
 		},
+		treasury: Default::default(),
+		// The council is initialised from the elections members.
+		council: Default::default(),
+		technical_committee: kumandra_runtime::TechnicalCommitteeConfig {
+			members: endowed_accounts.iter().take(3).cloned().collect(),
+			phantom: Default::default(),
+		},
+		elections: kumandra_runtime::ElectionsConfig {
+			members: endowed_accounts
+				.iter()
+				.take(3)
+				.cloned()
+				.map(|member| (member, kumandra_runtime::CandidacyBond::get()))
+				.collect(),
+		},
 	}
 }
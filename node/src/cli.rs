@@ -37,6 +37,10 @@ pub enum Subcommand {
 
 	/// Try some testing command against a specified runtime state.
 	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Plot disk space and prove the committed capacity as a storage provider.
+	#[clap(subcommand)]
+	Farm(crate::farm::FarmCmd),
 }
 
 #[derive(Debug, clap::Parser)]
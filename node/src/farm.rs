@@ -0,0 +1,313 @@
+//! The `farm` sub-command, used by storage providers to plot disk space, to answer the
+//! capacity challenges of the storage provider pallet and to attest their hardware.
+
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{BufReader, Read, Seek, SeekFrom, Write},
+	path::{Path, PathBuf},
+};
+
+use codec::Encode;
+use kumandra_primitive::{
+	plot::{self, CHUNKS, CHUNK_SIZE, PIECE_SIZE},
+	AccountId, BlockNumber, Signature,
+};
+use pallet_storage_provider::{attestation_payload, HardwareBenchmark, ProviderPolicy};
+use sc_cli::Result;
+use sp_core::{hexdisplay::HexDisplay, sr25519, Pair, H256};
+use sp_runtime::traits::{IdentifyAccount, Verify};
+
+/// File holding the plotted pieces.
+///
+/// The file is laid out by chunk offset rather than by piece: it holds the first chunk of every
+/// piece, then the second chunk of every piece and so on. A challenge audits one offset, which is
+/// then a single sequential read.
+const PLOT_FILE: &str = "plot.bin";
+
+/// File holding the seed of the benchmark key, which signs the hardware attestations.
+const BENCHMARK_KEY_FILE: &str = "benchmark.key";
+
+/// Number of pieces sealed in memory before their chunks are written to the plot.
+const PLOT_BATCH: u64 = 1 << 15;
+
+/// Sub-commands of the `farm` command.
+#[derive(Debug, clap::Subcommand)]
+pub enum FarmCmd {
+	/// Plot disk space and print the plot size to register on-chain.
+	Plot(PlotCmd),
+
+	/// Answer the current challenge of an existing plot.
+	Prove(ProveCmd),
+
+	/// Benchmark the hardware, check it against the provider policy and print the attestation
+	/// to publish on-chain, signed with the benchmark key of the node.
+	Attest(AttestCmd),
+}
+
+/// Parameters shared by the `farm` sub-commands working on a plot.
+#[derive(Debug, clap::Parser)]
+pub struct FarmerParams {
+	/// Directory holding the plot.
+	#[clap(long, parse(from_os_str))]
+	pub path: PathBuf,
+
+	/// Secret URI of the storage provider account the plot is derived from, e.g. `//Alice`.
+	#[clap(long)]
+	pub suri: String,
+}
+
+/// The `farm plot` command.
+#[derive(Debug, clap::Parser)]
+pub struct PlotCmd {
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub farmer: FarmerParams,
+
+	/// Size of the plot in MiB.
+	#[clap(long)]
+	pub size: u64,
+}
+
+/// The `farm prove` command.
+#[derive(Debug, clap::Parser)]
+pub struct ProveCmd {
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub farmer: FarmerParams,
+
+	/// Hash of the block the challenge was issued at, i.e. the `challenge_at` block of the plot.
+	#[clap(long)]
+	pub challenge: H256,
+}
+
+/// The `farm attest` command.
+#[derive(Debug, clap::Parser)]
+pub struct AttestCmd {
+	/// Directory holding the plot. The disk benchmarks run in this directory, and the benchmark
+	/// key is generated in it on the first run.
+	#[clap(long, parse(from_os_str))]
+	pub path: PathBuf,
+
+	/// The storage provider account the attestation is published by.
+	#[clap(long)]
+	pub account: AccountId,
+
+	/// Number of the current best block, which the measurement is bound to.
+	#[clap(long)]
+	pub at: BlockNumber,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub policy: PolicyParams,
+}
+
+/// The minimum hardware a storage provider requires of its own node before it accepts new
+/// deals. The chain enforces the policy set with `storageProvider.setProviderPolicy`.
+#[derive(Debug, clap::Parser)]
+pub struct PolicyParams {
+	/// Minimum sequential disk write throughput, in MB/s.
+	#[clap(long, default_value = "0")]
+	pub min_disk_sequential_write: u64,
+
+	/// Minimum random disk write throughput, in MB/s.
+	#[clap(long, default_value = "0")]
+	pub min_disk_random_write: u64,
+
+	/// Minimum free space next to the plot, in GiB.
+	#[clap(long, default_value = "0")]
+	pub min_free_space: u64,
+}
+
+impl PolicyParams {
+	/// The policy to set on-chain.
+	pub fn policy(&self) -> ProviderPolicy {
+		ProviderPolicy {
+			min_disk_sequential_write: self.min_disk_sequential_write,
+			min_disk_random_write: self.min_disk_random_write,
+			min_free_space: self.min_free_space.saturating_mul(1024 * 1024 * 1024),
+		}
+	}
+}
+
+impl FarmCmd {
+	/// Run the selected sub-command.
+	pub fn run(&self) -> Result<()> {
+		match self {
+			FarmCmd::Plot(cmd) => cmd.run(),
+			FarmCmd::Prove(cmd) => cmd.run(),
+			FarmCmd::Attest(cmd) => cmd.run(),
+		}
+	}
+}
+
+impl PlotCmd {
+	/// Seal the plot to disk and print its size.
+	pub fn run(&self) -> Result<()> {
+		let account = farmer_account(&self.farmer.suri)?;
+		let pieces = self.size.saturating_mul(1024 * 1024) / PIECE_SIZE as u64;
+		if pieces == 0 {
+			return Err("The plot has to hold at least one piece.".into())
+		}
+
+		fs::create_dir_all(&self.farmer.path)?;
+		let mut plot_file = OpenOptions::new()
+			.write(true)
+			.create(true)
+			.truncate(true)
+			.open(self.farmer.path.join(PLOT_FILE))?;
+		plot_file.set_len(pieces * PIECE_SIZE as u64)?;
+
+		let seed = plot::plot_seed(&account.encode());
+		for start in (0..pieces).step_by(PLOT_BATCH as usize) {
+			let batch = (start..pieces.min(start + PLOT_BATCH))
+				.map(|index| plot::encode_piece(&seed, index))
+				.collect::<Vec<_>>();
+
+			for offset in 0..CHUNKS as usize {
+				let chunks = batch
+					.iter()
+					.flat_map(|piece| &piece[offset * CHUNK_SIZE..(offset + 1) * CHUNK_SIZE])
+					.copied()
+					.collect::<Vec<_>>();
+				plot_file.seek(SeekFrom::Start(chunk_position(pieces, offset as u32, start)))?;
+				plot_file.write_all(&chunks)?;
+			}
+		}
+		plot_file.sync_all()?;
+
+		println!("Account: {}", account);
+		println!("Pieces: {}", pieces);
+		Ok(())
+	}
+}
+
+impl ProveCmd {
+	/// Audit the plot for the challenge and print the solutions answering it.
+	pub fn run(&self) -> Result<()> {
+		let account = farmer_account(&self.farmer.suri)?;
+		let plot_file = File::open(self.farmer.path.join(PLOT_FILE))?;
+		let plot_size = plot_file.metadata()?.len();
+		if plot_size == 0 || plot_size % PIECE_SIZE as u64 != 0 {
+			return Err(format!("{} is not a valid plot.", PLOT_FILE).into())
+		}
+		let pieces = plot_size / PIECE_SIZE as u64;
+
+		let randomness = self.challenge.as_bytes();
+		let offset = plot::audit_offset(randomness, &account.encode());
+		let mut reader = BufReader::new(plot_file);
+		reader.seek(SeekFrom::Start(chunk_position(pieces, offset, 0)))?;
+
+		let mut read_error = None;
+		let chunks = (0..pieces).map_while(|_| {
+			let mut chunk = [0u8; CHUNK_SIZE];
+			match reader.read_exact(&mut chunk) {
+				Ok(()) => Some(chunk),
+				Err(error) => {
+					read_error = Some(error);
+					None
+				},
+			}
+		});
+		let solutions = plot::find_solutions(randomness, pieces, chunks);
+		if let Some(error) = read_error {
+			return Err(error.into())
+		}
+
+		if solutions.len() < plot::required_solutions(pieces) as usize {
+			return Err("The plot holds too few solutions for this challenge, which cannot be \
+				answered. Register the plot again once the challenge has expired."
+				.into())
+		}
+
+		// Only the solutions are checked, a corrupted chunk elsewhere just goes unnoticed.
+		let seed = plot::plot_seed(&account.encode());
+		let mut reader = reader.into_inner();
+		for index in &solutions {
+			let mut chunk = [0u8; CHUNK_SIZE];
+			reader.seek(SeekFrom::Start(chunk_position(pieces, offset, *index)))?;
+			reader.read_exact(&mut chunk)?;
+			if chunk != plot::encode_chunk(&seed, *index, offset) {
+				return Err(
+					format!("Piece {} of the plot is corrupted, plot it again.", index).into()
+				)
+			}
+		}
+
+		let solutions = solutions.iter().map(ToString::to_string).collect::<Vec<_>>();
+		println!("Solutions: [{}]", solutions.join(", "));
+		Ok(())
+	}
+}
+
+impl AttestCmd {
+	/// Benchmark the hardware and print the signed attestation if it satisfies the policy.
+	pub fn run(&self) -> Result<()> {
+		fs::create_dir_all(&self.path)?;
+		let key = benchmark_key(&self.path)?;
+		let hwbench = sc_sysinfo::gather_hwbench(Some(&self.path));
+		let free_space = fs2::available_space(&self.path)?;
+		sc_sysinfo::print_hwbench(&hwbench);
+
+		let benchmark = HardwareBenchmark {
+			cpu_hashrate: hwbench.cpu_hashrate_score,
+			memory_memcpy: hwbench.memory_memcpy_score,
+			disk_sequential_write: hwbench.disk_sequential_write_score.unwrap_or_default(),
+			disk_random_write: hwbench.disk_random_write_score.unwrap_or_default(),
+			free_space,
+		};
+		let policy = self.policy.policy();
+		if !policy.check(&benchmark) {
+			return Err(format!(
+				"The node does not meet the storage provider policy: measured {:?}, required {:?}.",
+				benchmark, policy
+			)
+			.into())
+		}
+
+		let signature = key.sign(&attestation_payload(&self.account, &benchmark, &self.at));
+
+		println!(
+			"Benchmark key for `storageProvider.setBenchmarkKey`: 0x{}",
+			HexDisplay::from(&key.public().0)
+		);
+		println!("Policy for `storageProvider.setProviderPolicy`:");
+		println!("  minDiskSequentialWrite: {}", policy.min_disk_sequential_write);
+		println!("  minDiskRandomWrite: {}", policy.min_disk_random_write);
+		println!("  minFreeSpace: {}", policy.min_free_space);
+		println!("Attestation for `storageProvider.attestHardware`:");
+		println!("  cpuHashrate: {}", benchmark.cpu_hashrate);
+		println!("  memoryMemcpy: {}", benchmark.memory_memcpy);
+		println!("  diskSequentialWrite: {}", benchmark.disk_sequential_write);
+		println!("  diskRandomWrite: {}", benchmark.disk_random_write);
+		println!("  freeSpace: {}", benchmark.free_space);
+		println!("  measuredAt: {}", self.at);
+		println!("  signature: 0x{}", HexDisplay::from(&signature.0));
+		Ok(())
+	}
+}
+
+/// The benchmark key kept in `path`, generated on first use.
+fn benchmark_key(path: &Path) -> Result<sr25519::Pair> {
+	let file = path.join(BENCHMARK_KEY_FILE);
+	if file.exists() {
+		let seed = fs::read(&file)?;
+		return sr25519::Pair::from_seed_slice(&seed)
+			.map_err(|e| format!("Invalid benchmark key in {}: {:?}", file.display(), e).into())
+	}
+
+	let (pair, seed) = sr25519::Pair::generate();
+	fs::write(&file, seed)?;
+	Ok(pair)
+}
+
+/// The account of the storage provider identified by `suri`.
+fn farmer_account(suri: &str) -> Result<AccountId> {
+	let pair = sr25519::Pair::from_string(suri, None)
+		.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+	Ok(<Signature as Verify>::Signer::from(pair.public()).into_account())
+}
+
+/// The position in a plot of `pieces` pieces of the chunk at `offset` of the piece at `index`.
+fn chunk_position(pieces: u64, offset: u32, index: u64) -> u64 {
+	(u64::from(offset) * pieces + index) * CHUNK_SIZE as u64
+}
@@ -7,7 +7,9 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod farm;
 mod rpc;
+mod storage_metrics;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -219,7 +219,9 @@ where
 		+ sp_block_builder::BlockBuilder<Block>
 		+ forests_primitives_core::CollectCollationInfo<Block>
 		+ pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
-		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>
+		+ pallet_storage_deals_runtime_api::StorageStatsApi<Block, Balance>
+		+ pallet_storage_deals_runtime_api::StorageDealsApi<Block, AccountId>,
 	sc_client_api::StateBackendFor<TFullBackend<Block>, Block>: sp_api::StateBackend<BlakeTwo256>,
 	Executor: sc_executor::NativeExecutionDispatch + 'static,
 	RB: Fn(
@@ -340,6 +342,15 @@ where
 		}
 	}
 
+	if let Some(ref registry) = prometheus_registry {
+		let metrics = crate::storage_metrics::StorageMetrics::register(registry)?;
+		task_manager.spawn_handle().spawn(
+			"storage-metrics",
+			None,
+			crate::storage_metrics::run(client.clone(), metrics),
+		);
+	}
+
 	let announce_block = {
 		let network = network.clone();
 		Arc::new(move |hash, data| network.announce_block(hash, data))
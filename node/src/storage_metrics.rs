@@ -0,0 +1,98 @@
+//! Prometheus metrics of the storage network, refreshed from the runtime on every new best block.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use kumandra_primitive::{opaque::Block, Balance};
+use pallet_storage_deals_runtime_api::{StorageStats, StorageStatsApi};
+use sc_client_api::BlockchainEvents;
+use sp_api::ProvideRuntimeApi;
+use sp_runtime::generic::BlockId;
+use substrate_prometheus_endpoint::{register, Gauge, PrometheusError, Registry, F64, U64};
+
+/// Gauges mirroring the runtime's storage statistics.
+#[derive(Clone)]
+pub struct StorageMetrics {
+	committed_capacity: Gauge<U64>,
+	used_capacity: Gauge<U64>,
+	active_deals: Gauge<U64>,
+	average_price: Gauge<F64>,
+	proof_success_rate: Gauge<F64>,
+}
+
+impl StorageMetrics {
+	/// Create the gauges and register them with `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			committed_capacity: register(
+				Gauge::new(
+					"kumandra_storage_committed_capacity_bytes",
+					"Capacity of all registered plots",
+				)?,
+				registry,
+			)?,
+			used_capacity: register(
+				Gauge::new(
+					"kumandra_storage_used_capacity_bytes",
+					"Capacity committed to storage deals",
+				)?,
+				registry,
+			)?,
+			active_deals: register(
+				Gauge::new(
+					"kumandra_storage_active_deals",
+					"Number of storage deals that have not ended yet",
+				)?,
+				registry,
+			)?,
+			average_price: register(
+				Gauge::new(
+					"kumandra_storage_average_price",
+					"Average price per replica and block of the active deals, in 10^-12 KMD",
+				)?,
+				registry,
+			)?,
+			proof_success_rate: register(
+				Gauge::new(
+					"kumandra_storage_proof_success_rate",
+					"Share of the closed capacity challenges that were answered",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	fn report(&self, stats: &StorageStats<Balance>) {
+		self.committed_capacity.set(stats.committed_capacity);
+		self.used_capacity.set(stats.used_capacity);
+		self.active_deals.set(stats.active_deals.into());
+		self.average_price.set(stats.average_price as f64);
+		if let Some(rate) = stats.proof_success_rate {
+			self.proof_success_rate.set(rate.deconstruct() as f64 / 1_000_000.0);
+		}
+	}
+}
+
+/// Refresh `metrics` whenever a new best block is imported.
+pub async fn run<C>(client: Arc<C>, metrics: StorageMetrics)
+where
+	C: ProvideRuntimeApi<Block> + BlockchainEvents<Block>,
+	C::Api: StorageStatsApi<Block, Balance>,
+{
+	let mut imports = client.import_notification_stream();
+	while let Some(notification) = imports.next().await {
+		if !notification.is_new_best {
+			continue
+		}
+
+		match client.runtime_api().storage_stats(&BlockId::Hash(notification.hash)) {
+			Ok(stats) => metrics.report(&stats),
+			Err(e) => log::debug!(
+				target: "storage-metrics",
+				"Failed to read the storage statistics at {}: {:?}",
+				notification.hash,
+				e
+			),
+		}
+	}
+}
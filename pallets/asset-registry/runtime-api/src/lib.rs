@@ -0,0 +1,40 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime APIs of the foreign asset registry.
+
+use codec::{Codec, Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+use xcm::VersionedMultiLocation;
+
+/// A registered foreign asset, as displayed by wallets.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ForeignAsset<AssetId, Balance> {
+	/// The id of the asset on this chain.
+	pub asset_id: AssetId,
+	/// The location identifying the asset on other chains.
+	pub location: VersionedMultiLocation,
+	/// The user friendly name of the asset.
+	pub name: Vec<u8>,
+	/// The ticker symbol of the asset.
+	pub symbol: Vec<u8>,
+	/// The number of decimals the asset uses to represent one unit.
+	pub decimals: u8,
+	/// The minimum balance of the asset an account may hold.
+	pub existential_deposit: Balance,
+	/// The amount of the asset paying for one second of execution weight, if it can pay for
+	/// execution.
+	pub units_per_second: Option<u128>,
+}
+
+sp_api::decl_runtime_apis! {
+	/// The foreign assets known to the chain.
+	pub trait AssetRegistryApi<AssetId, Balance> where AssetId: Codec, Balance: Codec {
+		/// Every registered foreign asset.
+		fn foreign_assets() -> Vec<ForeignAsset<AssetId, Balance>>;
+
+		/// The foreign asset registered as `asset_id`, if any.
+		fn foreign_asset(asset_id: AssetId) -> Option<ForeignAsset<AssetId, Balance>>;
+	}
+}
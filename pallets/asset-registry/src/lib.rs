@@ -0,0 +1,401 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Registry of the foreign assets known to the chain.
+//!
+//! Governance registers a foreign asset with the XCM location it is identified by on other
+//! chains, its metadata and the price of execution weight in the asset. Registering creates the
+//! asset in the assets pallet, owned by `ForeignAssetOwner`, an account nobody controls, so that
+//! the asset is only ever minted and burned by XCM. The pallet converts between locations and
+//! asset ids for the XCM asset transactors, and the metadata lets wallets display foreign
+//! balances.
+//!
+//! Foreign assets take their ids from `ForeignAssetIds`. The runtime has to stop anyone else
+//! from creating assets with those ids, for example in its base call filter.
+//!
+//! Foreign assets with a price can pay for XCM execution through [`UsingRegisteredAssets`]. Other
+//! converters of asset locations can be kept off the foreign asset ids with
+//! [`ExceptForeignAssets`].
+pub use pallet::*;
+pub use trader::UsingRegisteredAssets;
+
+use frame_support::traits::Contains;
+use sp_std::{borrow::Borrow, marker::PhantomData};
+use xcm::latest::MultiLocation;
+use xcm_executor::traits::Convert;
+
+pub mod trader;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{fungibles, Contains},
+	};
+	use frame_system::pallet_prelude::*;
+	#[cfg(feature = "std")]
+	use serde::{Deserialize, Serialize};
+	use sp_std::{borrow::Borrow, boxed::Box, vec::Vec};
+	use xcm::{latest::MultiLocation, VersionedMultiLocation};
+	use xcm_executor::traits::Convert;
+
+	pub type BalanceOf<T> = <<T as Config>::Assets as fungibles::Inspect<
+		<T as frame_system::Config>::AccountId,
+	>>::Balance;
+
+	pub type AssetMetadataOf<T> =
+		AssetMetadata<BalanceOf<T>, BoundedVec<u8, <T as Config>::StringLimit>>;
+
+	/// The metadata of a foreign asset.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct AssetMetadata<Balance, BoundedString> {
+		/// The user friendly name of the asset.
+		pub name: BoundedString,
+		/// The ticker symbol of the asset.
+		pub symbol: BoundedString,
+		/// The number of decimals the asset uses to represent one unit.
+		pub decimals: u8,
+		/// The minimum balance of the asset an account may hold.
+		pub existential_deposit: Balance,
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Identifier of an asset.
+		type AssetId: Member + Parameter + Copy + MaxEncodedLen + MaybeSerializeDeserialize;
+
+		/// The assets foreign assets are created in.
+		type Assets: fungibles::Create<Self::AccountId, AssetId = Self::AssetId>;
+
+		/// The owner of foreign assets.
+		type ForeignAssetOwner: Get<Self::AccountId>;
+
+		/// The ids foreign assets may be registered with. No one else should be able to create
+		/// assets with them, or registrations could be front-run.
+		type ForeignAssetIds: Contains<Self::AssetId>;
+
+		/// The origin allowed to register foreign assets and update them.
+		type RegistryOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The maximum length of the name and symbol of an asset.
+		#[pallet::constant]
+		type StringLimit: Get<u32>;
+	}
+
+	/// The location of every foreign asset.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_location)]
+	pub type AssetLocations<T: Config> =
+		StorageMap<_, Twox64Concat, T::AssetId, MultiLocation, OptionQuery>;
+
+	/// The foreign asset at every registered location.
+	#[pallet::storage]
+	#[pallet::getter(fn location_asset)]
+	pub type LocationAssets<T: Config> =
+		StorageMap<_, Blake2_128Concat, MultiLocation, T::AssetId, OptionQuery>;
+
+	/// The metadata of every foreign asset.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_metadata)]
+	pub type Metadata<T: Config> =
+		StorageMap<_, Twox64Concat, T::AssetId, AssetMetadataOf<T>, OptionQuery>;
+
+	/// The amount of a foreign asset paying for one second of execution weight. Foreign assets
+	/// without a price cannot pay for execution.
+	#[pallet::storage]
+	#[pallet::getter(fn units_per_second)]
+	pub type UnitsPerSecond<T: Config> = StorageMap<_, Twox64Concat, T::AssetId, u128, OptionQuery>;
+
+	/// A foreign asset registered at genesis.
+	#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase", deny_unknown_fields))]
+	pub struct GenesisAsset<AssetId> {
+		/// The id the asset is created with.
+		pub asset_id: AssetId,
+		/// The location of the asset. Chain specs hold the hex of its SCALE encoding, as XCM
+		/// locations cannot be serialized otherwise.
+		#[cfg_attr(feature = "std", serde(with = "crate::scale_hex"))]
+		pub location: MultiLocation,
+		/// The user friendly name of the asset.
+		pub name: Vec<u8>,
+		/// The ticker symbol of the asset.
+		pub symbol: Vec<u8>,
+		/// The number of decimals the asset uses to represent one unit.
+		pub decimals: u8,
+		/// The minimum balance of the asset an account may hold.
+		pub existential_deposit: u128,
+		/// The amount of the asset paying for one second of execution weight, if any.
+		pub units_per_second: Option<u128>,
+		/// Whether holding the asset keeps an account alive.
+		pub is_sufficient: bool,
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// The foreign assets registered at genesis.
+		pub assets: Vec<GenesisAsset<T::AssetId>>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { assets: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for asset in &self.assets {
+				let metadata = AssetMetadata {
+					name: asset
+						.name
+						.clone()
+						.try_into()
+						.expect("genesis names are within the limit"),
+					symbol: asset
+						.symbol
+						.clone()
+						.try_into()
+						.expect("genesis symbols are within the limit"),
+					decimals: asset.decimals,
+					existential_deposit: asset
+						.existential_deposit
+						.try_into()
+						.map_err(|_| ())
+						.expect("genesis existential deposits fit in a balance"),
+				};
+				Pallet::<T>::do_register(
+					asset.asset_id,
+					asset.location.clone(),
+					metadata,
+					asset.units_per_second,
+					asset.is_sufficient,
+				)
+				.expect("genesis assets can be registered");
+			}
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A foreign asset was registered and created.
+		ForeignAssetRegistered { asset_id: T::AssetId, location: MultiLocation },
+		/// The metadata of a foreign asset was updated.
+		MetadataUpdated { asset_id: T::AssetId },
+		/// The price of execution weight in a foreign asset was updated.
+		UnitsPerSecondUpdated { asset_id: T::AssetId, units_per_second: Option<u128> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The location uses an XCM version that cannot be converted.
+		BadVersion,
+		/// The name or symbol is longer than `StringLimit`.
+		BadMetadata,
+		/// An asset is already registered with this id.
+		AssetAlreadyRegistered,
+		/// An asset is already registered at this location.
+		LocationAlreadyRegistered,
+		/// No foreign asset is registered with this id.
+		UnknownAsset,
+		/// The id is not one of the `ForeignAssetIds`.
+		NotForeignAssetId,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register the foreign asset at `location` as `asset_id` and create it.
+		///
+		/// Accounts holding less than the existential deposit of the asset are reaped. Holding a
+		/// sufficient asset keeps an account alive without any native balance.
+		#[pallet::weight(50_000 + T::DbWeight::get().reads_writes(3, 6))]
+		pub fn register_foreign_asset(
+			origin: OriginFor<T>,
+			asset_id: T::AssetId,
+			location: Box<VersionedMultiLocation>,
+			metadata: AssetMetadataOf<T>,
+			units_per_second: Option<u128>,
+			is_sufficient: bool,
+		) -> DispatchResult {
+			T::RegistryOrigin::ensure_origin(origin)?;
+			let location =
+				MultiLocation::try_from(*location).map_err(|()| Error::<T>::BadVersion)?;
+
+			Self::do_register(asset_id, location, metadata, units_per_second, is_sufficient)
+		}
+
+		/// Update the name, symbol and decimals of a foreign asset.
+		///
+		/// The existential deposit of an asset cannot be changed once it is created.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_asset_metadata(
+			origin: OriginFor<T>,
+			asset_id: T::AssetId,
+			name: Vec<u8>,
+			symbol: Vec<u8>,
+			decimals: u8,
+		) -> DispatchResult {
+			T::RegistryOrigin::ensure_origin(origin)?;
+			let name: BoundedVec<_, _> = name.try_into().map_err(|_| Error::<T>::BadMetadata)?;
+			let symbol: BoundedVec<_, _> =
+				symbol.try_into().map_err(|_| Error::<T>::BadMetadata)?;
+
+			Metadata::<T>::try_mutate(asset_id, |metadata| -> DispatchResult {
+				let metadata = metadata.as_mut().ok_or(Error::<T>::UnknownAsset)?;
+				metadata.name = name;
+				metadata.symbol = symbol;
+				metadata.decimals = decimals;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::MetadataUpdated { asset_id });
+			Ok(())
+		}
+
+		/// Set the price of execution weight in a foreign asset, or stop accepting the asset for
+		/// execution with `None`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_units_per_second(
+			origin: OriginFor<T>,
+			asset_id: T::AssetId,
+			units_per_second: Option<u128>,
+		) -> DispatchResult {
+			T::RegistryOrigin::ensure_origin(origin)?;
+			ensure!(AssetLocations::<T>::contains_key(asset_id), Error::<T>::UnknownAsset);
+
+			UnitsPerSecond::<T>::set(asset_id, units_per_second);
+			Self::deposit_event(Event::UnitsPerSecondUpdated { asset_id, units_per_second });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The location and metadata of every foreign asset, with the price of execution weight
+		/// in the asset.
+		///
+		/// Iterates over all assets, so it is meant for runtime APIs rather than extrinsics.
+		pub fn foreign_assets() -> Vec<(T::AssetId, MultiLocation, AssetMetadataOf<T>, Option<u128>)>
+		{
+			AssetLocations::<T>::iter()
+				.filter_map(|(asset_id, location)| {
+					let metadata = Metadata::<T>::get(asset_id)?;
+					Some((asset_id, location, metadata, UnitsPerSecond::<T>::get(asset_id)))
+				})
+				.collect()
+		}
+
+		/// The amount of the foreign asset at `location` paying for one second of execution
+		/// weight, if it can pay for execution.
+		pub fn units_per_second_at(location: &MultiLocation) -> Option<u128> {
+			LocationAssets::<T>::get(location).and_then(UnitsPerSecond::<T>::get)
+		}
+
+		fn do_register(
+			asset_id: T::AssetId,
+			location: MultiLocation,
+			metadata: AssetMetadataOf<T>,
+			units_per_second: Option<u128>,
+			is_sufficient: bool,
+		) -> DispatchResult {
+			ensure!(T::ForeignAssetIds::contains(&asset_id), Error::<T>::NotForeignAssetId);
+			ensure!(
+				!AssetLocations::<T>::contains_key(asset_id),
+				Error::<T>::AssetAlreadyRegistered
+			);
+			ensure!(
+				!LocationAssets::<T>::contains_key(&location),
+				Error::<T>::LocationAlreadyRegistered
+			);
+
+			T::Assets::create(
+				asset_id,
+				T::ForeignAssetOwner::get(),
+				is_sufficient,
+				metadata.existential_deposit,
+			)?;
+			AssetLocations::<T>::insert(asset_id, &location);
+			LocationAssets::<T>::insert(&location, asset_id);
+			Metadata::<T>::insert(asset_id, metadata);
+			if let Some(units_per_second) = units_per_second {
+				UnitsPerSecond::<T>::insert(asset_id, units_per_second);
+			}
+
+			Self::deposit_event(Event::ForeignAssetRegistered { asset_id, location });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Convert<MultiLocation, T::AssetId> for Pallet<T> {
+		fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<T::AssetId, ()> {
+			LocationAssets::<T>::get(location.borrow()).ok_or(())
+		}
+
+		fn reverse_ref(asset_id: impl Borrow<T::AssetId>) -> Result<MultiLocation, ()> {
+			AssetLocations::<T>::get(asset_id.borrow()).ok_or(())
+		}
+	}
+}
+
+/// Converts asset locations with `Converter`, except those of foreign asset ids, which only the
+/// registry converts.
+///
+/// Converters such as `AsPrefixedGeneralIndex` map any id to a location of this chain. Without
+/// the filter, a foreign asset would also be reachable as a local asset and be transacted as if
+/// this chain were its reserve.
+pub struct ExceptForeignAssets<T, Converter>(PhantomData<(T, Converter)>);
+impl<T: Config, Converter: Convert<MultiLocation, T::AssetId>> Convert<MultiLocation, T::AssetId>
+	for ExceptForeignAssets<T, Converter>
+{
+	fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<T::AssetId, ()> {
+		let asset_id = Converter::convert_ref(location)?;
+		if T::ForeignAssetIds::contains(&asset_id) {
+			return Err(())
+		}
+		Ok(asset_id)
+	}
+
+	fn reverse_ref(asset_id: impl Borrow<T::AssetId>) -> Result<MultiLocation, ()> {
+		if T::ForeignAssetIds::contains(asset_id.borrow()) {
+			return Err(())
+		}
+		Converter::reverse_ref(asset_id)
+	}
+}
+
+/// Serializes values as the hex of their SCALE encoding.
+#[cfg(feature = "std")]
+mod scale_hex {
+	use codec::{Decode, Encode};
+	use serde::{de::Error, Deserializer, Serializer};
+
+	pub fn serialize<S: Serializer, V: Encode>(
+		value: &V,
+		serializer: S,
+	) -> Result<S::Ok, S::Error> {
+		sp_core::bytes::serialize(&value.encode(), serializer)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>, V: Decode>(
+		deserializer: D,
+	) -> Result<V, D::Error> {
+		let encoded = sp_core::bytes::deserialize(deserializer)?;
+		V::decode(&mut &encoded[..]).map_err(|error| D::Error::custom(error.to_string()))
+	}
+}
@@ -0,0 +1,138 @@
+use crate as pallet_asset_registry;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU32, ConstU64, Contains, Everything},
+};
+use frame_system::{self as system, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+use xcm::latest::MultiAsset;
+use xcm_executor::traits::TakeRevenue;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
+		AssetRegistry: pallet_asset_registry::{Pallet, Call, Storage, Config<T>, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<u64>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type ApprovalDeposit = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+/// The account owning foreign assets.
+pub const FOREIGN_ASSET_OWNER: u64 = 99;
+
+/// Foreign assets take the ids below 100.
+pub struct ForeignAssetIds;
+impl Contains<u32> for ForeignAssetIds {
+	fn contains(id: &u32) -> bool {
+		*id < 100
+	}
+}
+
+impl pallet_asset_registry::Config for Test {
+	type Event = Event;
+	type AssetId = u32;
+	type Assets = Assets;
+	type ForeignAssetOwner = ConstU64<FOREIGN_ASSET_OWNER>;
+	type ForeignAssetIds = ForeignAssetIds;
+	type RegistryOrigin = EnsureRoot<u64>;
+	type StringLimit = ConstU32<16>;
+}
+
+thread_local! {
+	static REVENUE: RefCell<Vec<MultiAsset>> = RefCell::new(Vec::new());
+}
+
+/// The execution fees taken by [`RecordRevenue`].
+pub fn revenue() -> Vec<MultiAsset> {
+	REVENUE.with(|revenue| revenue.borrow().clone())
+}
+
+/// Records the execution fees paid with foreign assets.
+pub struct RecordRevenue;
+impl TakeRevenue for RecordRevenue {
+	fn take_revenue(revenue: MultiAsset) {
+		REVENUE.with(|taken| taken.borrow_mut().push(revenue));
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
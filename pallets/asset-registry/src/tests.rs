@@ -0,0 +1,290 @@
+use crate::{
+	mock::*, AssetMetadata, AssetMetadataOf, Error, Event as AssetRegistryEvent,
+	ExceptForeignAssets, GenesisAsset, GenesisConfig, UsingRegisteredAssets,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{fungibles::Inspect, GenesisBuild},
+	weights::constants::WEIGHT_PER_SECOND,
+};
+use sp_runtime::DispatchError;
+use sp_std::borrow::Borrow;
+use xcm::{
+	latest::{Error as XcmError, Junction::*, Junctions::*, MultiAsset, MultiLocation},
+	VersionedMultiLocation,
+};
+use xcm_executor::{
+	traits::{Convert, WeightTrader},
+	Assets,
+};
+
+fn stablecoin() -> MultiLocation {
+	MultiLocation::new(1, X3(Parachain(2001), PalletInstance(50), GeneralIndex(1984)))
+}
+
+fn usdt() -> AssetMetadataOf<Test> {
+	AssetMetadata {
+		name: b"Tether USD".to_vec().try_into().unwrap(),
+		symbol: b"USDT".to_vec().try_into().unwrap(),
+		decimals: 6,
+		existential_deposit: 10,
+	}
+}
+
+fn register(asset_id: u32, location: MultiLocation) -> sp_runtime::DispatchResult {
+	AssetRegistry::register_foreign_asset(
+		Origin::root(),
+		asset_id,
+		Box::new(VersionedMultiLocation::V1(location)),
+		usdt(),
+		Some(1_000),
+		true,
+	)
+}
+
+#[test]
+fn registering_creates_the_asset() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(1, stablecoin()));
+		System::assert_last_event(
+			AssetRegistryEvent::ForeignAssetRegistered { asset_id: 1, location: stablecoin() }
+				.into(),
+		);
+
+		assert_eq!(Assets::minimum_balance(1), 10);
+		assert_eq!(AssetRegistry::asset_metadata(1), Some(usdt()));
+		assert_eq!(AssetRegistry::units_per_second_at(&stablecoin()), Some(1_000));
+		assert_eq!(AssetRegistry::foreign_assets(), vec![(1, stablecoin(), usdt(), Some(1_000))]);
+		assert_eq!(pallet_assets::Asset::<Test>::get(1).unwrap().owner, FOREIGN_ASSET_OWNER);
+		assert_eq!(AssetRegistry::asset_location(1), Some(stablecoin()));
+		assert_eq!(<AssetRegistry as Convert<_, _>>::convert_ref(stablecoin()), Ok(1));
+		assert_eq!(
+			<AssetRegistry as Convert<MultiLocation, u32>>::reverse_ref(1),
+			Ok(stablecoin())
+		);
+		assert_eq!(
+			<AssetRegistry as Convert<_, u32>>::convert_ref(MultiLocation::parent()),
+			Err(())
+		);
+	});
+}
+
+#[test]
+fn genesis_assets_are_registered() {
+	let asset = GenesisAsset {
+		asset_id: 1,
+		location: stablecoin(),
+		name: b"Tether USD".to_vec(),
+		symbol: b"USDT".to_vec(),
+		decimals: 6,
+		existential_deposit: 10,
+		units_per_second: Some(1_000),
+		is_sufficient: true,
+	};
+	let json = serde_json::to_string(&asset).unwrap();
+	assert_eq!(serde_json::from_str::<GenesisAsset<u32>>(&json).unwrap(), asset);
+
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisConfig::<Test> { assets: vec![asset] }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	sp_io::TestExternalities::new(storage).execute_with(|| {
+		assert_eq!(Assets::minimum_balance(1), 10);
+		assert_eq!(AssetRegistry::asset_metadata(1), Some(usdt()));
+		assert_eq!(AssetRegistry::units_per_second_at(&stablecoin()), Some(1_000));
+		assert_eq!(<AssetRegistry as Convert<_, _>>::convert_ref(stablecoin()), Ok(1));
+	});
+}
+
+/// Converts between ids and the `GeneralIndex` locations of this chain, like
+/// `AsPrefixedGeneralIndex` does.
+struct GeneralIndexes;
+impl Convert<MultiLocation, u32> for GeneralIndexes {
+	fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<u32, ()> {
+		match location.borrow() {
+			MultiLocation { parents: 0, interior: X1(GeneralIndex(id)) } =>
+				(*id).try_into().map_err(|_| ()),
+			_ => Err(()),
+		}
+	}
+
+	fn reverse_ref(id: impl Borrow<u32>) -> Result<MultiLocation, ()> {
+		Ok(GeneralIndex((*id.borrow()).into()).into())
+	}
+}
+
+#[test]
+fn foreign_asset_ids_are_not_converted_as_local_assets() {
+	type LocalAssets = ExceptForeignAssets<Test, GeneralIndexes>;
+	let local = |id: u128| -> MultiLocation { GeneralIndex(id).into() };
+
+	assert_eq!(LocalAssets::convert_ref(local(100)), Ok(100));
+	assert_eq!(LocalAssets::reverse_ref(100), Ok(local(100)));
+	assert_eq!(LocalAssets::convert_ref(local(0)), Err(()));
+	assert_eq!(LocalAssets::convert_ref(local(99)), Err(()));
+	assert_eq!(LocalAssets::reverse_ref(0), Err(()));
+	assert_eq!(LocalAssets::reverse_ref(99), Err(()));
+}
+
+#[test]
+fn assets_and_locations_are_registered_once() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(1, stablecoin()));
+		assert_noop!(register(1, MultiLocation::parent()), Error::<Test>::AssetAlreadyRegistered);
+		assert_noop!(register(2, stablecoin()), Error::<Test>::LocationAlreadyRegistered);
+	});
+}
+
+#[test]
+fn assets_are_registered_with_foreign_asset_ids() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(register(100, stablecoin()), Error::<Test>::NotForeignAssetId);
+		assert_ok!(register(99, stablecoin()));
+	});
+}
+
+#[test]
+fn existing_assets_cannot_be_registered() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 1, 1, true, 1));
+		assert_noop!(register(1, stablecoin()), pallet_assets::Error::<Test>::InUse);
+	});
+}
+
+#[test]
+fn only_the_registry_origin_can_register() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetRegistry::register_foreign_asset(
+				Origin::signed(1),
+				1,
+				Box::new(VersionedMultiLocation::V1(stablecoin())),
+				usdt(),
+				None,
+				true,
+			),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn metadata_can_be_updated() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetRegistry::set_asset_metadata(Origin::root(), 1, b"USD".to_vec(), vec![], 6),
+			Error::<Test>::UnknownAsset
+		);
+		assert_ok!(register(1, stablecoin()));
+
+		assert_noop!(
+			AssetRegistry::set_asset_metadata(Origin::root(), 1, vec![b'a'; 17], vec![], 6),
+			Error::<Test>::BadMetadata
+		);
+		assert_ok!(AssetRegistry::set_asset_metadata(
+			Origin::root(),
+			1,
+			b"Tether".to_vec(),
+			b"USDt".to_vec(),
+			8
+		));
+		System::assert_last_event(AssetRegistryEvent::MetadataUpdated { asset_id: 1 }.into());
+
+		let metadata = AssetRegistry::asset_metadata(1).unwrap();
+		assert_eq!(metadata.name.into_inner(), b"Tether".to_vec());
+		assert_eq!(metadata.symbol.into_inner(), b"USDt".to_vec());
+		assert_eq!(metadata.decimals, 8);
+		assert_eq!(metadata.existential_deposit, 10);
+	});
+}
+
+#[test]
+fn assets_without_a_price_cannot_pay_for_execution() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetRegistry::set_units_per_second(Origin::root(), 1, None),
+			Error::<Test>::UnknownAsset
+		);
+		assert_ok!(register(1, stablecoin()));
+
+		assert_ok!(AssetRegistry::set_units_per_second(Origin::root(), 1, None));
+		System::assert_last_event(
+			AssetRegistryEvent::UnitsPerSecondUpdated { asset_id: 1, units_per_second: None }
+				.into(),
+		);
+		assert_eq!(AssetRegistry::units_per_second_at(&stablecoin()), None);
+
+		assert_ok!(AssetRegistry::set_units_per_second(Origin::root(), 1, Some(5)));
+		assert_eq!(AssetRegistry::units_per_second_at(&stablecoin()), Some(5));
+		assert_eq!(AssetRegistry::units_per_second_at(&MultiLocation::parent()), None);
+	});
+}
+
+#[test]
+fn registered_assets_pay_for_execution() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(1, stablecoin()));
+		// One unit of the stablecoin per unit of weight.
+		assert_ok!(AssetRegistry::set_units_per_second(
+			Origin::root(),
+			1,
+			Some(WEIGHT_PER_SECOND as u128)
+		));
+		let usdt = |amount: u128| -> MultiAsset { (stablecoin(), amount).into() };
+		let relay = |amount: u128| -> MultiAsset { (MultiLocation::parent(), amount).into() };
+
+		let mut trader = UsingRegisteredAssets::<Test, RecordRevenue>::new();
+		let mut payment = Assets::from(relay(1_000));
+		payment.subsume(usdt(1_000));
+		let unused = trader.buy_weight(300, payment).unwrap();
+		let mut expected = Assets::from(relay(1_000));
+		expected.subsume(usdt(700));
+		assert_eq!(unused, expected);
+
+		assert_eq!(trader.buy_weight(200, usdt(100).into()), Err(XcmError::TooExpensive));
+		assert_eq!(trader.buy_weight(200, usdt(200).into()), Ok(Assets::new()));
+		assert_eq!(trader.refund_weight(100), Some(usdt(100)));
+		assert_eq!(trader.refund_weight(1_000), Some(usdt(400)));
+		assert_eq!(trader.refund_weight(100), None);
+		assert_ok!(trader.buy_weight(50, usdt(50).into()));
+		drop(trader);
+		assert_eq!(revenue(), vec![usdt(50)]);
+	});
+}
+
+#[test]
+fn execution_prices_are_rounded_up() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(1, stablecoin()));
+		// One unit of the stablecoin per second of execution.
+		assert_ok!(AssetRegistry::set_units_per_second(Origin::root(), 1, Some(1)));
+		let usdt = |amount: u128| -> MultiAsset { (stablecoin(), amount).into() };
+
+		let mut trader = UsingRegisteredAssets::<Test, RecordRevenue>::new();
+		assert_eq!(trader.buy_weight(1, usdt(1).into()), Ok(Assets::new()));
+		assert_eq!(
+			trader.buy_weight(WEIGHT_PER_SECOND + 1, usdt(1).into()),
+			Err(XcmError::TooExpensive)
+		);
+		assert_eq!(trader.buy_weight(WEIGHT_PER_SECOND + 1, usdt(2).into()), Ok(Assets::new()));
+		// Refunds are rounded down.
+		assert_eq!(trader.refund_weight(WEIGHT_PER_SECOND), Some(usdt(1)));
+		assert_eq!(trader.refund_weight(1), None);
+		drop(trader);
+		assert_eq!(revenue(), vec![usdt(2)]);
+	});
+}
+
+#[test]
+fn assets_without_a_price_cannot_buy_execution() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(1, stablecoin()));
+		assert_ok!(AssetRegistry::set_units_per_second(Origin::root(), 1, None));
+
+		let mut trader = UsingRegisteredAssets::<Test, RecordRevenue>::new();
+		let payment: MultiAsset = (stablecoin(), 1_000).into();
+		assert_eq!(trader.buy_weight(300, payment.into()), Err(XcmError::TooExpensive));
+		drop(trader);
+		assert!(revenue().is_empty());
+	});
+}
@@ -0,0 +1,102 @@
+//! Buying XCM execution weight with any registered foreign asset.
+
+use crate::{Config, Pallet};
+use frame_support::weights::{constants::WEIGHT_PER_SECOND, Weight};
+use sp_std::{marker::PhantomData, vec, vec::Vec};
+use xcm::latest::prelude::*;
+use xcm_executor::{
+	traits::{TakeRevenue, WeightTrader},
+	Assets,
+};
+
+/// Buys execution weight with the first foreign asset of the payment that has a price in the
+/// registry. The weight is charged at the units per second of the asset, and `R` takes what was
+/// paid once execution is over.
+///
+/// Later purchases within the same execution have to be paid in the same asset.
+pub struct UsingRegisteredAssets<T: Config, R: TakeRevenue> {
+	/// The weight bought so far.
+	weight: Weight,
+	/// The location of the asset paying for execution, with the amount paid so far.
+	paid: Option<(MultiLocation, u128)>,
+	_phantom: PhantomData<(T, R)>,
+}
+
+impl<T: Config, R: TakeRevenue> UsingRegisteredAssets<T, R> {
+	/// The price of `weight`, rounded up so that no execution is free.
+	fn price(units_per_second: u128, weight: Weight) -> u128 {
+		let units = units_per_second.saturating_mul(weight as u128);
+		let per_second = WEIGHT_PER_SECOND as u128;
+		units / per_second + u128::from(units % per_second != 0)
+	}
+
+	/// The refund for `weight`, rounded down so that it never exceeds the price.
+	fn refund(units_per_second: u128, weight: Weight) -> u128 {
+		units_per_second.saturating_mul(weight as u128) / (WEIGHT_PER_SECOND as u128)
+	}
+}
+
+impl<T: Config, R: TakeRevenue> WeightTrader for UsingRegisteredAssets<T, R> {
+	fn new() -> Self {
+		Self { weight: 0, paid: None, _phantom: PhantomData }
+	}
+
+	fn buy_weight(&mut self, weight: Weight, payment: Assets) -> Result<Assets, XcmError> {
+		let candidates: Vec<MultiLocation> = match &self.paid {
+			Some((location, _)) => vec![location.clone()],
+			None => payment
+				.fungible_assets_iter()
+				.filter_map(|asset| match asset.id {
+					Concrete(location) => Some(location),
+					Abstract(_) => None,
+				})
+				.collect(),
+		};
+
+		for location in candidates {
+			let units_per_second = match Pallet::<T>::units_per_second_at(&location) {
+				Some(units_per_second) => units_per_second,
+				None => continue,
+			};
+			let amount = Self::price(units_per_second, weight);
+			let unused = if amount == 0 {
+				payment.clone()
+			} else {
+				match payment.clone().checked_sub((location.clone(), amount).into()) {
+					Ok(unused) => unused,
+					Err(_) => continue,
+				}
+			};
+
+			let paid = self.paid.as_ref().map_or(0, |(_, paid)| *paid);
+			self.paid = Some((location, paid.saturating_add(amount)));
+			self.weight = self.weight.saturating_add(weight);
+			return Ok(unused)
+		}
+		Err(XcmError::TooExpensive)
+	}
+
+	fn refund_weight(&mut self, weight: Weight) -> Option<MultiAsset> {
+		let (location, paid) = self.paid.as_mut()?;
+		let units_per_second = Pallet::<T>::units_per_second_at(location)?;
+		let weight = weight.min(self.weight);
+		let amount = Self::refund(units_per_second, weight).min(*paid);
+		self.weight -= weight;
+		*paid -= amount;
+		if amount > 0 {
+			Some((location.clone(), amount).into())
+		} else {
+			None
+		}
+	}
+}
+
+impl<T: Config, R: TakeRevenue> Drop for UsingRegisteredAssets<T, R> {
+	fn drop(&mut self) {
+		if let Some((location, paid)) = self.paid.take() {
+			if paid > 0 {
+				R::take_revenue((location, paid).into());
+			}
+		}
+	}
+}
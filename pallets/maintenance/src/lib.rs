@@ -0,0 +1,339 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Emergency call filtering and maintenance mode.
+//!
+//! Governance can pause whole pallets or single calls of a pallet, identified by the names
+//! reported by `GetCallMetadata`, and put the chain into maintenance mode, which filters every
+//! call but the `WhitelistedCalls` of the runtime. The pallet is meant to be used as the
+//! `BaseCallFilter` of the runtime, and its own calls are never filtered so that it cannot lock
+//! itself out.
+//!
+//! Entering maintenance mode can also suspend XCM execution. The XCMP queue is suspended through
+//! `XcmExecutionManager`, while downward messages are kept by this pallet, which has to be the
+//! `DmpMessageHandler` of the parachain system. They are handed to the wrapped handler, in the
+//! order they were received, once normal operation resumes: ahead of the messages received in a
+//! later block, or when the block has weight left.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use forests_primitives_core::{
+		relay_chain::BlockNumber as RelayBlockNumber, DmpMessageHandler,
+	};
+	use frame_support::{
+		dispatch::CallMetadata,
+		pallet_prelude::*,
+		traits::{Contains, GetCallMetadata, PalletInfoAccess},
+		weights::Weight,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_std::vec::Vec;
+
+	/// The name of a pallet or of a call, as reported by `GetCallMetadata`.
+	pub type NameOf<T> = BoundedVec<u8, <T as Config>::MaxNameLength>;
+
+	/// Suspends and resumes the execution of XCM messages from sibling chains.
+	pub trait PauseXcmExecution {
+		/// Stop executing incoming messages, which are kept until execution resumes.
+		fn suspend_xcm_execution() -> DispatchResult;
+		/// Execute incoming messages again.
+		fn resume_xcm_execution() -> DispatchResult;
+	}
+
+	impl PauseXcmExecution for () {
+		fn suspend_xcm_execution() -> DispatchResult {
+			Ok(())
+		}
+
+		fn resume_xcm_execution() -> DispatchResult {
+			Ok(())
+		}
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The origin allowed to pause calls and to enter or leave maintenance mode.
+		type MaintenanceOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Calls that are never filtered, such as inherents and governance.
+		type WhitelistedCalls: Contains<<Self as frame_system::Config>::Call>;
+
+		/// Suspends the execution of messages from sibling chains.
+		type XcmExecutionManager: PauseXcmExecution;
+
+		/// Handles downward messages while XCM execution is not suspended.
+		type DmpMessageHandler: DmpMessageHandler;
+
+		/// The maximum length of a pallet or call name.
+		#[pallet::constant]
+		type MaxNameLength: Get<u32>;
+	}
+
+	/// The pallets all calls of which are filtered.
+	#[pallet::storage]
+	pub type PausedPallets<T: Config> = StorageMap<_, Twox64Concat, NameOf<T>, (), OptionQuery>;
+
+	/// The single calls that are filtered, by pallet and call name.
+	#[pallet::storage]
+	pub type PausedCalls<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, NameOf<T>, Twox64Concat, NameOf<T>, (), OptionQuery>;
+
+	/// Whether the chain is in maintenance mode.
+	#[pallet::storage]
+	#[pallet::getter(fn maintenance_mode)]
+	pub type MaintenanceMode<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Whether XCM execution was suspended when entering maintenance mode.
+	#[pallet::storage]
+	#[pallet::getter(fn xcm_suspended)]
+	pub type XcmSuspended<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The downward messages received while XCM execution was suspended, by index.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	pub type QueuedDownwardMessages<T: Config> =
+		StorageMap<_, Twox64Concat, u64, (RelayBlockNumber, Vec<u8>), OptionQuery>;
+
+	/// The index of the oldest queued downward message and the index of the next one.
+	#[pallet::storage]
+	pub type DownwardQueue<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// All calls of a pallet are filtered.
+		PalletPaused { pallet: NameOf<T> },
+		/// The calls of a pallet are no longer filtered.
+		PalletUnpaused { pallet: NameOf<T> },
+		/// A call is filtered.
+		CallPaused { pallet: NameOf<T>, call: NameOf<T> },
+		/// A call is no longer filtered.
+		CallUnpaused { pallet: NameOf<T>, call: NameOf<T> },
+		/// The chain entered maintenance mode.
+		MaintenanceEntered { xcm_suspended: bool },
+		/// The chain left maintenance mode.
+		NormalOperationResumed,
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The calls of this pallet cannot be paused.
+		CannotPause,
+		/// The pallet or call is paused already.
+		AlreadyPaused,
+		/// The pallet or call is not paused.
+		NotPaused,
+		/// The chain is in maintenance mode already.
+		AlreadyInMaintenance,
+		/// The chain is not in maintenance mode.
+		NotInMaintenance,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::service_downward_queue(remaining_weight)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Filter all calls of `pallet`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn pause_pallet(origin: OriginFor<T>, pallet: NameOf<T>) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(!Self::is_self(&pallet), Error::<T>::CannotPause);
+			ensure!(!PausedPallets::<T>::contains_key(&pallet), Error::<T>::AlreadyPaused);
+
+			PausedPallets::<T>::insert(&pallet, ());
+			Self::deposit_event(Event::PalletPaused { pallet });
+			Ok(())
+		}
+
+		/// Stop filtering the calls of `pallet`. Calls paused one by one stay filtered.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn unpause_pallet(origin: OriginFor<T>, pallet: NameOf<T>) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(PausedPallets::<T>::take(&pallet).is_some(), Error::<T>::NotPaused);
+
+			Self::deposit_event(Event::PalletUnpaused { pallet });
+			Ok(())
+		}
+
+		/// Filter the call `call` of `pallet`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn pause_call(
+			origin: OriginFor<T>,
+			pallet: NameOf<T>,
+			call: NameOf<T>,
+		) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(!Self::is_self(&pallet), Error::<T>::CannotPause);
+			ensure!(!PausedCalls::<T>::contains_key(&pallet, &call), Error::<T>::AlreadyPaused);
+
+			PausedCalls::<T>::insert(&pallet, &call, ());
+			Self::deposit_event(Event::CallPaused { pallet, call });
+			Ok(())
+		}
+
+		/// Stop filtering the call `call` of `pallet`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn unpause_call(
+			origin: OriginFor<T>,
+			pallet: NameOf<T>,
+			call: NameOf<T>,
+		) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(PausedCalls::<T>::take(&pallet, &call).is_some(), Error::<T>::NotPaused);
+
+			Self::deposit_event(Event::CallUnpaused { pallet, call });
+			Ok(())
+		}
+
+		/// Filter every call but the whitelisted ones. With `suspend_xcm`, incoming XCM messages
+		/// are kept and only executed once normal operation resumes.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn enter_maintenance(origin: OriginFor<T>, suspend_xcm: bool) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(!MaintenanceMode::<T>::get(), Error::<T>::AlreadyInMaintenance);
+
+			if suspend_xcm {
+				T::XcmExecutionManager::suspend_xcm_execution()?;
+				XcmSuspended::<T>::put(true);
+			}
+			MaintenanceMode::<T>::put(true);
+			Self::deposit_event(Event::MaintenanceEntered { xcm_suspended: suspend_xcm });
+			Ok(())
+		}
+
+		/// Leave maintenance mode and resume XCM execution if it was suspended. Paused pallets
+		/// and calls stay filtered.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn resume_normal_operation(origin: OriginFor<T>) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(MaintenanceMode::<T>::get(), Error::<T>::NotInMaintenance);
+
+			if XcmSuspended::<T>::take() {
+				T::XcmExecutionManager::resume_xcm_execution()?;
+			}
+			MaintenanceMode::<T>::put(false);
+			Self::deposit_event(Event::NormalOperationResumed);
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Whether `pallet` names this pallet.
+		fn is_self(pallet: &[u8]) -> bool {
+			pallet == <Self as PalletInfoAccess>::name().as_bytes()
+		}
+
+		/// Whether the call of `function` of `pallet` is paused on its own or with its pallet.
+		pub fn is_paused(pallet: &str, function: &str) -> bool {
+			let pallet: NameOf<T> = match pallet.as_bytes().to_vec().try_into() {
+				Ok(pallet) => pallet,
+				// Names above the maximum length cannot be paused.
+				Err(_) => return false,
+			};
+			if PausedPallets::<T>::contains_key(&pallet) {
+				return true
+			}
+			match NameOf::<T>::try_from(function.as_bytes().to_vec()) {
+				Ok(function) => PausedCalls::<T>::contains_key(&pallet, &function),
+				Err(_) => false,
+			}
+		}
+
+		/// Queue downward messages behind the ones kept already.
+		fn queue_downward(iter: impl Iterator<Item = (RelayBlockNumber, Vec<u8>)>) -> Weight {
+			let (head, mut tail) = DownwardQueue::<T>::get();
+			let mut writes = 1;
+			for message in iter {
+				QueuedDownwardMessages::<T>::insert(tail, message);
+				tail += 1;
+				writes += 1;
+			}
+			DownwardQueue::<T>::put((head, tail));
+			T::DbWeight::get().reads_writes(1, writes)
+		}
+
+		/// Hand the queued downward messages to `T::DmpMessageHandler`, as far as
+		/// `remaining_weight` allows reading them.
+		fn service_downward_queue(remaining_weight: Weight) -> Weight {
+			let mut used = T::DbWeight::get().reads(2);
+			let (mut head, tail) = DownwardQueue::<T>::get();
+			if XcmSuspended::<T>::get() || head == tail {
+				return used
+			}
+
+			let per_message = T::DbWeight::get().reads_writes(1, 1);
+			let mut messages = Vec::new();
+			while head < tail && used.saturating_add(per_message) <= remaining_weight {
+				if let Some(message) = QueuedDownwardMessages::<T>::take(head) {
+					messages.push(message);
+				}
+				head += 1;
+				used = used.saturating_add(per_message);
+			}
+			DownwardQueue::<T>::put((head, tail));
+			used = used.saturating_add(T::DbWeight::get().writes(1));
+
+			let handled = T::DmpMessageHandler::handle_dmp_messages(
+				messages.into_iter(),
+				remaining_weight.saturating_sub(used),
+			);
+			used.saturating_add(handled)
+		}
+	}
+
+	impl<T: Config> Contains<<T as frame_system::Config>::Call> for Pallet<T>
+	where
+		<T as frame_system::Config>::Call: GetCallMetadata,
+	{
+		fn contains(call: &<T as frame_system::Config>::Call) -> bool {
+			if T::WhitelistedCalls::contains(call) {
+				return true
+			}
+			let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+			if Self::is_self(pallet_name.as_bytes()) {
+				return true
+			}
+			!MaintenanceMode::<T>::get() && !Self::is_paused(pallet_name, function_name)
+		}
+	}
+
+	impl<T: Config> DmpMessageHandler for Pallet<T> {
+		fn handle_dmp_messages(
+			iter: impl Iterator<Item = (RelayBlockNumber, Vec<u8>)>,
+			limit: Weight,
+		) -> Weight {
+			if XcmSuspended::<T>::get() {
+				return T::DbWeight::get().reads(1).saturating_add(Self::queue_downward(iter))
+			}
+
+			// Messages queued earlier are handed over first to keep the order of execution, so
+			// the new ones are only handed over once the queue is empty.
+			let used = Self::service_downward_queue(limit);
+			let (head, tail) = DownwardQueue::<T>::get();
+			if head != tail {
+				return used.saturating_add(Self::queue_downward(iter))
+			}
+			used.saturating_add(T::DmpMessageHandler::handle_dmp_messages(
+				iter,
+				limit.saturating_sub(used),
+			))
+		}
+	}
+}
@@ -0,0 +1,151 @@
+use crate as pallet_maintenance;
+use forests_primitives_core::{relay_chain::BlockNumber as RelayBlockNumber, DmpMessageHandler};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU32, Contains},
+	weights::{RuntimeDbWeight, Weight},
+};
+use frame_system::{self as system, EnsureRoot};
+use pallet_maintenance::PauseXcmExecution;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchResult,
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Maintenance: pallet_maintenance::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	// Storage accesses weigh little next to the limits of the tests, but enough to bound them.
+	pub const TestDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 1 };
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = Maintenance;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = TestDbWeight;
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static XCM_SUSPENDED: RefCell<bool> = RefCell::new(false);
+	static HANDLED_MESSAGES: RefCell<Vec<(RelayBlockNumber, Vec<u8>)>> = RefCell::new(Vec::new());
+}
+
+/// Whether [`MockXcmExecutionManager`] suspended XCM execution.
+pub fn xcm_execution_suspended() -> bool {
+	XCM_SUSPENDED.with(|suspended| *suspended.borrow())
+}
+
+/// The downward messages [`MockDmpHandler`] received.
+pub fn handled_messages() -> Vec<(RelayBlockNumber, Vec<u8>)> {
+	HANDLED_MESSAGES.with(|messages| messages.borrow().clone())
+}
+
+pub struct MockXcmExecutionManager;
+impl PauseXcmExecution for MockXcmExecutionManager {
+	fn suspend_xcm_execution() -> DispatchResult {
+		XCM_SUSPENDED.with(|suspended| *suspended.borrow_mut() = true);
+		Ok(())
+	}
+
+	fn resume_xcm_execution() -> DispatchResult {
+		XCM_SUSPENDED.with(|suspended| *suspended.borrow_mut() = false);
+		Ok(())
+	}
+}
+
+pub struct MockDmpHandler;
+impl DmpMessageHandler for MockDmpHandler {
+	fn handle_dmp_messages(
+		iter: impl Iterator<Item = (RelayBlockNumber, Vec<u8>)>,
+		_limit: Weight,
+	) -> Weight {
+		HANDLED_MESSAGES.with(|messages| messages.borrow_mut().extend(iter));
+		0
+	}
+}
+
+/// Remarks with an event stay available in maintenance mode.
+pub struct WhitelistedCalls;
+impl Contains<Call> for WhitelistedCalls {
+	fn contains(call: &Call) -> bool {
+		matches!(call, Call::System(frame_system::Call::remark_with_event { .. }))
+	}
+}
+
+impl pallet_maintenance::Config for Test {
+	type Event = Event;
+	type MaintenanceOrigin = EnsureRoot<u64>;
+	type WhitelistedCalls = WhitelistedCalls;
+	type XcmExecutionManager = MockXcmExecutionManager;
+	type DmpMessageHandler = MockDmpHandler;
+	type MaxNameLength = ConstU32<32>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	XCM_SUSPENDED.with(|suspended| *suspended.borrow_mut() = false);
+	HANDLED_MESSAGES.with(|messages| messages.borrow_mut().clear());
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 100), (2, 100)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,195 @@
+use crate::{mock::*, Error, Event as MaintenanceEvent, NameOf};
+use forests_primitives_core::DmpMessageHandler;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Contains, Hooks},
+};
+use sp_runtime::{traits::Dispatchable, DispatchError};
+
+fn name(name: &str) -> NameOf<Test> {
+	name.as_bytes().to_vec().try_into().unwrap()
+}
+
+fn transfer() -> Call {
+	Call::Balances(pallet_balances::Call::transfer { dest: 2, value: 10 })
+}
+
+fn transfer_keep_alive() -> Call {
+	Call::Balances(pallet_balances::Call::transfer_keep_alive { dest: 2, value: 10 })
+}
+
+fn remark_with_event() -> Call {
+	Call::System(frame_system::Call::remark_with_event { remark: vec![1] })
+}
+
+#[test]
+fn pausing_a_pallet_filters_all_its_calls() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Maintenance::pause_pallet(Origin::root(), name("Balances")));
+		System::assert_last_event(
+			MaintenanceEvent::PalletPaused { pallet: name("Balances") }.into(),
+		);
+		assert!(!Maintenance::contains(&transfer()));
+		assert!(!Maintenance::contains(&transfer_keep_alive()));
+		assert!(Maintenance::contains(&Call::System(frame_system::Call::remark {
+			remark: vec![1]
+		})));
+		assert_noop!(
+			transfer().dispatch(Origin::signed(1)).map_err(|e| e.error),
+			DispatchError::from(frame_system::Error::<Test>::CallFiltered)
+		);
+
+		assert_noop!(
+			Maintenance::pause_pallet(Origin::root(), name("Balances")),
+			Error::<Test>::AlreadyPaused
+		);
+		assert_ok!(Maintenance::unpause_pallet(Origin::root(), name("Balances")));
+		assert!(Maintenance::contains(&transfer()));
+		assert_ok!(transfer().dispatch(Origin::signed(1)).map_err(|e| e.error));
+		assert_eq!(Balances::free_balance(2), 110);
+	});
+}
+
+#[test]
+fn pausing_a_call_filters_only_that_call() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Maintenance::pause_call(Origin::root(), name("Balances"), name("transfer")));
+		System::assert_last_event(
+			MaintenanceEvent::CallPaused { pallet: name("Balances"), call: name("transfer") }
+				.into(),
+		);
+		assert!(!Maintenance::contains(&transfer()));
+		assert!(Maintenance::contains(&transfer_keep_alive()));
+
+		// Unpausing the pallet leaves single calls paused.
+		assert_ok!(Maintenance::pause_pallet(Origin::root(), name("Balances")));
+		assert_ok!(Maintenance::unpause_pallet(Origin::root(), name("Balances")));
+		assert!(!Maintenance::contains(&transfer()));
+
+		assert_ok!(Maintenance::unpause_call(Origin::root(), name("Balances"), name("transfer")));
+		assert!(Maintenance::contains(&transfer()));
+		assert_noop!(
+			Maintenance::unpause_call(Origin::root(), name("Balances"), name("transfer")),
+			Error::<Test>::NotPaused
+		);
+	});
+}
+
+#[test]
+fn only_the_maintenance_origin_can_pause() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Maintenance::pause_pallet(Origin::signed(1), name("Balances")),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Maintenance::pause_call(Origin::signed(1), name("Balances"), name("transfer")),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Maintenance::enter_maintenance(Origin::signed(1), false),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn the_maintenance_pallet_cannot_be_paused() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Maintenance::pause_pallet(Origin::root(), name("Maintenance")),
+			Error::<Test>::CannotPause
+		);
+		assert_noop!(
+			Maintenance::pause_call(
+				Origin::root(),
+				name("Maintenance"),
+				name("resume_normal_operation")
+			),
+			Error::<Test>::CannotPause
+		);
+
+		assert_ok!(Maintenance::enter_maintenance(Origin::root(), false));
+		let resume = Call::Maintenance(crate::Call::resume_normal_operation {});
+		assert!(Maintenance::contains(&resume));
+	});
+}
+
+#[test]
+fn maintenance_mode_filters_all_but_whitelisted_calls() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Maintenance::enter_maintenance(Origin::root(), false));
+		System::assert_last_event(
+			MaintenanceEvent::MaintenanceEntered { xcm_suspended: false }.into(),
+		);
+		assert!(Maintenance::maintenance_mode());
+		assert!(!Maintenance::xcm_suspended());
+		assert!(!xcm_execution_suspended());
+
+		assert!(!Maintenance::contains(&transfer()));
+		assert!(Maintenance::contains(&remark_with_event()));
+		assert_noop!(
+			Maintenance::enter_maintenance(Origin::root(), true),
+			Error::<Test>::AlreadyInMaintenance
+		);
+
+		assert_ok!(Maintenance::resume_normal_operation(Origin::root()));
+		System::assert_last_event(MaintenanceEvent::NormalOperationResumed.into());
+		assert!(Maintenance::contains(&transfer()));
+		assert_noop!(
+			Maintenance::resume_normal_operation(Origin::root()),
+			Error::<Test>::NotInMaintenance
+		);
+	});
+}
+
+#[test]
+fn downward_messages_are_kept_while_xcm_is_suspended() {
+	new_test_ext().execute_with(|| {
+		Maintenance::handle_dmp_messages(vec![(1, vec![1])].into_iter(), 1_000);
+		assert_eq!(handled_messages(), vec![(1, vec![1])]);
+
+		assert_ok!(Maintenance::enter_maintenance(Origin::root(), true));
+		assert!(Maintenance::xcm_suspended());
+		assert!(xcm_execution_suspended());
+
+		Maintenance::handle_dmp_messages(vec![(2, vec![2]), (3, vec![3])].into_iter(), 1_000);
+		Maintenance::on_idle(1, 1_000);
+		assert_eq!(handled_messages(), vec![(1, vec![1])]);
+
+		assert_ok!(Maintenance::resume_normal_operation(Origin::root()));
+		assert!(!xcm_execution_suspended());
+
+		// Messages arriving after the resume are handled after the kept ones.
+		Maintenance::handle_dmp_messages(vec![(4, vec![4])].into_iter(), 1_000);
+		assert_eq!(
+			handled_messages(),
+			vec![(1, vec![1]), (2, vec![2]), (3, vec![3]), (4, vec![4])]
+		);
+		assert_eq!(crate::DownwardQueue::<Test>::get(), (2, 2));
+	});
+}
+
+#[test]
+fn kept_messages_are_handled_ahead_of_new_ones_within_the_limit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Maintenance::enter_maintenance(Origin::root(), true));
+		Maintenance::handle_dmp_messages(
+			vec![(1, vec![1]), (2, vec![2]), (3, vec![3])].into_iter(),
+			1_000,
+		);
+		assert_ok!(Maintenance::resume_normal_operation(Origin::root()));
+
+		// The limit covers one kept message only: the rest and the new messages are kept.
+		Maintenance::handle_dmp_messages(vec![(4, vec![4])].into_iter(), 5);
+		assert_eq!(handled_messages(), vec![(1, vec![1])]);
+		assert_eq!(crate::DownwardQueue::<Test>::get(), (1, 4));
+
+		Maintenance::handle_dmp_messages(vec![(5, vec![5])].into_iter(), 1_000);
+		assert_eq!(
+			handled_messages(),
+			vec![(1, vec![1]), (2, vec![2]), (3, vec![3]), (4, vec![4]), (5, vec![5])]
+		);
+		assert_eq!(crate::DownwardQueue::<Test>::get(), (4, 4));
+	});
+}
@@ -0,0 +1,109 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime upgrades authorized by governance.
+//!
+//! An upgrade takes two steps. Governance first authorizes the hash of the new runtime code,
+//! which is recorded in an event so that every upgrade can be audited. Anyone can then upload
+//! the code matching the hash. Before it is enacted, the code has to keep the `spec_name` of the
+//! running runtime and increase its `spec_version`, as checked by
+//! [`frame_system::Pallet::can_set_code`]. The code is handed to the `OnSetCode` hook of the
+//! system pallet, which schedules the upgrade with the relay chain on parachains.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+	use frame_system::{pallet_prelude::*, SetCode};
+	use sp_runtime::traits::Hash;
+	use sp_std::vec::Vec;
+
+	/// The weight of hashing one byte of runtime code and of reading the version out of it.
+	pub const WEIGHT_PER_CODE_BYTE: Weight = 10_000;
+
+	/// The weight of enacting runtime code of `length` bytes.
+	pub fn enact_weight<T: frame_system::Config>(length: usize) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(WEIGHT_PER_CODE_BYTE.saturating_mul(length as Weight))
+			.saturating_add(T::DbWeight::get().reads_writes(3, 4))
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The origin allowed to authorize runtime upgrades.
+		type AuthorizeOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	/// The hash of the runtime code that can be enacted.
+	#[pallet::storage]
+	#[pallet::getter(fn authorized_upgrade)]
+	pub type AuthorizedUpgrade<T: Config> = StorageValue<_, T::Hash, OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The runtime code with hash `code_hash` can be enacted. It replaces any previously
+		/// authorized code.
+		UpgradeAuthorized { code_hash: T::Hash },
+		/// The authorized runtime code was uploaded by `who` and enacted.
+		UpgradeEnacted { code_hash: T::Hash, who: T::AccountId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// No runtime upgrade is authorized.
+		NothingAuthorized,
+		/// The hash of the code does not match the authorized hash.
+		Unauthorized,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Authorize the runtime code with hash `code_hash` to be enacted.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn authorize_upgrade(origin: OriginFor<T>, code_hash: T::Hash) -> DispatchResult {
+			T::AuthorizeOrigin::ensure_origin(origin)?;
+
+			AuthorizedUpgrade::<T>::put(code_hash);
+			Self::deposit_event(Event::UpgradeAuthorized { code_hash });
+			Ok(())
+		}
+
+		/// Upload and enact the authorized runtime code.
+		///
+		/// The code has to keep the `spec_name` of the running runtime and increase its
+		/// `spec_version`. The call is weighed by the length of the code, and the caller does not
+		/// pay for a successful upgrade.
+		#[pallet::weight((enact_weight::<T>(code.len()), DispatchClass::Operational))]
+		pub fn enact_authorized_upgrade(
+			origin: OriginFor<T>,
+			code: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let authorized = AuthorizedUpgrade::<T>::get().ok_or(Error::<T>::NothingAuthorized)?;
+			let code_hash = T::Hashing::hash(&code);
+			ensure!(code_hash == authorized, Error::<T>::Unauthorized);
+
+			frame_system::Pallet::<T>::can_set_code(&code)?;
+			T::OnSetCode::set_code(code)?;
+
+			AuthorizedUpgrade::<T>::kill();
+			Self::deposit_event(Event::UpgradeEnacted { code_hash, who });
+			Ok(Pays::No.into())
+		}
+	}
+}
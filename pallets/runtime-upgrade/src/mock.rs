@@ -0,0 +1,99 @@
+use crate as pallet_runtime_upgrade;
+use codec::Encode;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU32, Everything},
+};
+use frame_system::{self as system, EnsureRoot};
+use sp_core::{traits::ReadRuntimeVersionExt, H256};
+use sp_runtime::{
+	create_runtime_str,
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_version::RuntimeVersion;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		RuntimeUpgrade: pallet_runtime_upgrade::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	pub const Version: RuntimeVersion = RuntimeVersion {
+		spec_name: create_runtime_str!("kumandra"),
+		impl_name: create_runtime_str!("kumandra"),
+		authoring_version: 1,
+		spec_version: 1,
+		impl_version: 0,
+		apis: sp_version::create_apis_vec!([]),
+		transaction_version: 1,
+		state_version: 1,
+	};
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = Version;
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_runtime_upgrade::Config for Test {
+	type Event = Event;
+	type AuthorizeOrigin = EnsureRoot<u64>;
+}
+
+/// Reports the version of any code as the version it was created with.
+struct ReadRuntimeVersion(Vec<u8>);
+impl sp_core::traits::ReadRuntimeVersion for ReadRuntimeVersion {
+	fn read_runtime_version(
+		&self,
+		_wasm_code: &[u8],
+		_ext: &mut dyn sp_core::traits::Externalities,
+	) -> Result<Vec<u8>, String> {
+		Ok(self.0.clone())
+	}
+}
+
+// Build genesis storage according to the mock runtime, with any uploaded code being of
+// `spec_name` and `spec_version`.
+pub fn new_test_ext(spec_name: &'static str, spec_version: u32) -> sp_io::TestExternalities {
+	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	let version = RuntimeVersion { spec_name: spec_name.into(), spec_version, ..Version::get() };
+	ext.register_extension(ReadRuntimeVersionExt::new(ReadRuntimeVersion(version.encode())));
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,106 @@
+use crate::{mock::*, Error, Event as RuntimeUpgradeEvent, WEIGHT_PER_CODE_BYTE};
+use frame_support::{assert_noop, assert_ok, weights::GetDispatchInfo};
+use sp_core::{storage::well_known_keys, H256};
+use sp_runtime::{
+	traits::{BlakeTwo256, Hash},
+	DispatchError,
+};
+
+fn code() -> Vec<u8> {
+	vec![1, 2, 3, 4]
+}
+
+fn code_hash() -> H256 {
+	BlakeTwo256::hash(&code())
+}
+
+#[test]
+fn authorized_code_can_be_enacted_by_anyone() {
+	new_test_ext("kumandra", 2).execute_with(|| {
+		assert_ok!(RuntimeUpgrade::authorize_upgrade(Origin::root(), code_hash()));
+		System::assert_last_event(
+			RuntimeUpgradeEvent::UpgradeAuthorized { code_hash: code_hash() }.into(),
+		);
+		assert_eq!(RuntimeUpgrade::authorized_upgrade(), Some(code_hash()));
+
+		assert_ok!(RuntimeUpgrade::enact_authorized_upgrade(Origin::signed(1), code()));
+		System::assert_has_event(frame_system::Event::CodeUpdated.into());
+		System::assert_last_event(
+			RuntimeUpgradeEvent::UpgradeEnacted { code_hash: code_hash(), who: 1 }.into(),
+		);
+		assert_eq!(sp_io::storage::get(well_known_keys::CODE), Some(code()));
+		assert_eq!(RuntimeUpgrade::authorized_upgrade(), None);
+	});
+}
+
+#[test]
+fn only_the_authorize_origin_can_authorize() {
+	new_test_ext("kumandra", 2).execute_with(|| {
+		assert_noop!(
+			RuntimeUpgrade::authorize_upgrade(Origin::signed(1), code_hash()),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn only_the_authorized_code_can_be_enacted() {
+	new_test_ext("kumandra", 2).execute_with(|| {
+		assert_noop!(
+			RuntimeUpgrade::enact_authorized_upgrade(Origin::signed(1), code()),
+			Error::<Test>::NothingAuthorized
+		);
+
+		assert_ok!(RuntimeUpgrade::authorize_upgrade(Origin::root(), code_hash()));
+		assert_noop!(
+			RuntimeUpgrade::enact_authorized_upgrade(Origin::signed(1), vec![5, 6]),
+			Error::<Test>::Unauthorized
+		);
+
+		// A new authorization replaces the previous one.
+		assert_ok!(RuntimeUpgrade::authorize_upgrade(Origin::root(), BlakeTwo256::hash(&[5, 6])));
+		assert_noop!(
+			RuntimeUpgrade::enact_authorized_upgrade(Origin::signed(1), code()),
+			Error::<Test>::Unauthorized
+		);
+		assert_ok!(RuntimeUpgrade::enact_authorized_upgrade(Origin::signed(1), vec![5, 6]));
+	});
+}
+
+#[test]
+fn enacted_code_must_increase_the_spec_version() {
+	new_test_ext("kumandra", 1).execute_with(|| {
+		assert_ok!(RuntimeUpgrade::authorize_upgrade(Origin::root(), code_hash()));
+		assert_noop!(
+			RuntimeUpgrade::enact_authorized_upgrade(Origin::signed(1), code()),
+			frame_system::Error::<Test>::SpecVersionNeedsToIncrease
+		);
+		assert_eq!(RuntimeUpgrade::authorized_upgrade(), Some(code_hash()));
+	});
+}
+
+#[test]
+fn enacted_code_must_keep_the_spec_name() {
+	new_test_ext("other", 2).execute_with(|| {
+		assert_ok!(RuntimeUpgrade::authorize_upgrade(Origin::root(), code_hash()));
+		assert_noop!(
+			RuntimeUpgrade::enact_authorized_upgrade(Origin::signed(1), code()),
+			frame_system::Error::<Test>::InvalidSpecName
+		);
+	});
+}
+
+#[test]
+fn enacting_is_weighed_by_the_code_length() {
+	new_test_ext("kumandra", 2).execute_with(|| {
+		let weight = |code: Vec<u8>| {
+			crate::Call::<Test>::enact_authorized_upgrade { code }
+				.get_dispatch_info()
+				.weight
+		};
+		assert_eq!(weight(vec![0; 1_000]) - weight(code()), 996 * WEIGHT_PER_CODE_BYTE);
+		assert!(
+			weight(vec![0; 1_000]) < <Test as frame_system::Config>::BlockWeights::get().max_block
+		);
+	});
+}
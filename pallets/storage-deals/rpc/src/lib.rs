@@ -0,0 +1,92 @@
+//! RPC methods of the storage deals pallet.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_storage_deals_runtime_api::StorageDealsApi as StorageDealsRuntimeApi;
+
+/// Storage deal matching RPC methods.
+#[rpc(client, server)]
+pub trait StorageDealsApi<BlockHash, AccountId> {
+	/// The providers a deal of `size` bytes can currently be matched with. With `verified_only`,
+	/// only providers with a verified identity are returned.
+	#[method(name = "storageDeals_eligibleProviders")]
+	fn eligible_providers(
+		&self,
+		size: u64,
+		verified_only: bool,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<AccountId>>;
+
+	/// Whether the data of the deal `deal_id` may still be served. It may not once the deal has
+	/// ended, at the end of its duration or by a deletion request.
+	#[method(name = "storageDeals_isServable")]
+	fn is_servable(&self, deal_id: u64, at: Option<BlockHash>) -> RpcResult<bool>;
+}
+
+/// Implementation of [`StorageDealsApiServer`] backed by the runtime API.
+pub struct StorageDeals<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> StorageDeals<C, Block> {
+	/// Create a new instance serving requests from `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error code of failed runtime API calls.
+const RUNTIME_ERROR: i32 = 1;
+
+impl<C, Block, AccountId> StorageDealsApiServer<<Block as BlockT>::Hash, AccountId>
+	for StorageDeals<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: StorageDealsRuntimeApi<Block, AccountId>,
+	AccountId: Codec + Send + Sync + 'static,
+{
+	fn eligible_providers(
+		&self,
+		size: u64,
+		verified_only: bool,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.eligible_providers(&at, size, verified_only).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				RUNTIME_ERROR,
+				"Unable to query the eligible providers.",
+				Some(e.to_string()),
+			))
+			.into()
+		})
+	}
+
+	fn is_servable(&self, deal_id: u64, at: Option<<Block as BlockT>::Hash>) -> RpcResult<bool> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.is_servable(&at, deal_id).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				RUNTIME_ERROR,
+				"Unable to query whether the deal is servable.",
+				Some(e.to_string()),
+			))
+			.into()
+		})
+	}
+}
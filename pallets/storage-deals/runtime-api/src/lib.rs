@@ -0,0 +1,42 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime APIs of the storage network.
+
+use codec::{Codec, Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::{Permill, RuntimeDebug};
+use sp_std::vec::Vec;
+
+/// A snapshot of the storage network.
+#[derive(Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct StorageStats<Balance> {
+	/// The capacity, in bytes, of all registered plots.
+	pub committed_capacity: u64,
+	/// The capacity, in bytes, committed to deals.
+	pub used_capacity: u64,
+	/// The number of deals that have not ended yet.
+	pub active_deals: u32,
+	/// The average price per replica and block of the active deals.
+	pub average_price: Balance,
+	/// The share of the closed capacity challenges that were answered, if any has closed.
+	pub proof_success_rate: Option<Permill>,
+}
+
+sp_api::decl_runtime_apis! {
+	/// Statistics of the storage network.
+	pub trait StorageStatsApi<Balance> where Balance: Codec {
+		/// The current statistics of the storage network.
+		fn storage_stats() -> StorageStats<Balance>;
+	}
+
+	/// Storage deal matching.
+	pub trait StorageDealsApi<AccountId> where AccountId: Codec {
+		/// The providers a deal of `size` bytes can currently be matched with. With
+		/// `verified_only`, only providers with a verified identity are returned.
+		fn eligible_providers(size: u64, verified_only: bool) -> Vec<AccountId>;
+
+		/// Whether the data of the deal `deal_id` may still be served. It may not once the deal
+		/// has ended, at the end of its duration or by a deletion request.
+		fn is_servable(deal_id: u64) -> bool;
+	}
+}
@@ -0,0 +1,1047 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Storage deals between clients and storage providers.
+//!
+//! A client asks for a number of replicas of its data and names the providers it is willing to
+//! use. The matcher picks the replicas among those candidates that accept deals, have enough
+//! unused proven capacity and ask no more than the offered price, while honouring the diversity
+//! rules of the client. Providers only take deals once they set an asking price for the currency
+//! or asset the deal is paid in. Clients can also restrict the match to providers with a verified
+//! identity. The price of the whole deal is reserved up front and paid out to the providers once
+//! the deal has ended. Deals can also be paid in an asset, such as a stablecoin received over XCM,
+//! in which case the price is held by the pallet account. Every provider is paid on its own:
+//! payments that cannot be transferred, for example while the asset is frozen, are held by the
+//! pallet account and owed, to be claimed later, and never hold up the rest of a settlement. Until
+//! a deal ends, the owner can transfer it, with its price, to another account.
+//!
+//! The owner can end a deal early by requesting the deletion of its data. The unused part of the
+//! price is refunded right away. Each provider then has `DeletionWindow` blocks to confirm the
+//! deletion on-chain, which releases its share of the payment for the blocks served. The share of
+//! providers failing to confirm in time goes back to the owner. From the request on, the data of
+//! the deal must no longer be served, see [`Pallet::is_servable`].
+//!
+//! Sibling chains, and their users, can buy storage over XCM with a `Transact` of
+//! [`Pallet::create_deal_with_response`] keeping their XCM origin. The deal is paid for by the
+//! sovereign account of the chain, or by the account derived from the user. The id of the deal,
+//! or the reason it could not be created, is sent back to the chain as the response to one of its
+//! queries.
+pub use pallet::*;
+
+pub mod placement;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use crate::placement::{self, Candidate, PlacementError};
+	use frame_support::{
+		pallet_prelude::*,
+		storage::with_storage_layer,
+		traits::{
+			fungibles, BalanceStatus, Currency, ExistenceRequirement, NamedReservableCurrency,
+			PalletInfoAccess,
+		},
+		weights::Weight,
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use kumandra_primitive::storage::{StorageProviders, VerifiedIdentities};
+	use sp_core::H256;
+	use sp_runtime::{
+		traits::{AccountIdConversion, CheckedMul, SaturatedConversion, Saturating, Zero},
+		ArithmeticError, ModuleError,
+	};
+	use sp_std::{collections::btree_set::BTreeSet, vec, vec::Vec};
+	use xcm::latest::prelude::*;
+	use xcm_executor::traits::{Convert, InvertLocation};
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Identifier of a deal.
+	pub type DealId = u64;
+
+	/// The amount of data asking prices are set for.
+	const GIB: u64 = 1 << 30;
+
+	pub type DealOf<T> = Deal<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::AssetId,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+		BoundedVec<<T as frame_system::Config>::AccountId, <T as Config>::MaxReplicas>,
+	>;
+
+	/// How a client wants the replicas of a deal to be spread.
+	#[derive(
+		Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+	)]
+	pub struct DiversityRules {
+		/// Minimum number of distinct regions holding a replica.
+		pub min_regions: u32,
+		/// Minimum number of distinct operators holding a replica.
+		pub min_operators: u32,
+		/// Maximum number of replicas within a single region.
+		pub max_per_region: Option<u32>,
+		/// Maximum number of replicas held by a single operator.
+		pub max_per_operator: Option<u32>,
+	}
+
+	/// A storage deal.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Deal<AccountId, AssetId, Balance, BlockNumber, Providers> {
+		/// The client owning the data.
+		pub owner: AccountId,
+		/// Commitment to the stored data.
+		pub data_root: H256,
+		/// Size of the data in bytes.
+		pub size: u64,
+		/// The providers holding a replica each.
+		pub providers: Providers,
+		/// The price per block paid to every provider.
+		pub price_per_block: Balance,
+		/// The asset the deal is paid in, or `None` for `Currency`.
+		pub asset: Option<AssetId>,
+		/// The block the deal started at.
+		pub start: BlockNumber,
+		/// The block the deal ends at.
+		pub end: BlockNumber,
+	}
+
+	/// Running totals over a set of deals, kept for the statistics of the storage network.
+	#[derive(
+		Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+	)]
+	pub struct DealTotals<Balance> {
+		/// The number of deals.
+		pub deals: u32,
+		/// The number of deals paid in `Currency`.
+		pub priced: u32,
+		/// The sum of the price per block of the deals paid in `Currency`.
+		pub price: Balance,
+	}
+
+	impl<Balance: Saturating> DealTotals<Balance> {
+		/// Count a deal at `price_per_block`, which is only summed if it is paid in `Currency`.
+		fn add(&mut self, price_per_block: Balance, in_currency: bool) {
+			self.deals = self.deals.saturating_add(1);
+			if in_currency {
+				self.priced = self.priced.saturating_add(1);
+				self.price = self.price.saturating_add(price_per_block);
+			}
+		}
+
+		/// Stop counting the deals of `other`.
+		fn remove(&mut self, other: Self) {
+			self.deals = self.deals.saturating_sub(other.deals);
+			self.priced = self.priced.saturating_sub(other.priced);
+			self.price = self.price.saturating_sub(other.price);
+		}
+	}
+
+	pub type DeletionOf<T> = DeletionRequest<
+		DealOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+		BoundedVec<<T as frame_system::Config>::AccountId, <T as Config>::MaxReplicas>,
+	>;
+
+	/// A request to delete the data of a deal before it ended.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct DeletionRequest<Deal, BlockNumber, Providers> {
+		/// The deal, ending at the block the deletion was requested at.
+		pub deal: Deal,
+		/// The block the deletion was requested at.
+		pub requested_at: BlockNumber,
+		/// The providers that have not confirmed the deletion yet.
+		pub pending: Providers,
+	}
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency deals are paid in by default.
+		type Currency: NamedReservableCurrency<Self::AccountId, ReserveIdentifier = [u8; 8]>;
+
+		/// Identifier of the assets deals can be paid in instead of `Currency`.
+		type AssetId: Member + Parameter + Copy + MaxEncodedLen;
+
+		/// The assets deals can be paid in instead of `Currency`.
+		type Assets: fungibles::Transfer<
+			Self::AccountId,
+			AssetId = Self::AssetId,
+			Balance = BalanceOf<Self>,
+		>;
+
+		/// The id of the account holding the price of deals paid in assets, and of the reserve
+		/// holding the price of deals paid in `Currency`.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// The storage providers deals are matched with.
+		type Providers: StorageProviders<Self::AccountId>;
+
+		/// The accounts with a verified identity.
+		type Identities: VerifiedIdentities<Self::AccountId>;
+
+		/// The maximum number of replicas of a deal.
+		#[pallet::constant]
+		type MaxReplicas: Get<u32>;
+
+		/// The maximum number of candidates a client can name for a deal.
+		#[pallet::constant]
+		type MaxCandidates: Get<u32>;
+
+		/// The shortest duration of a deal.
+		#[pallet::constant]
+		type MinDuration: Get<Self::BlockNumber>;
+
+		/// The longest duration of a deal.
+		#[pallet::constant]
+		type MaxDuration: Get<Self::BlockNumber>;
+
+		/// Number of blocks providers have to confirm a deletion.
+		#[pallet::constant]
+		type DeletionWindow: Get<Self::BlockNumber>;
+
+		/// The origin of calls made over XCM, giving the location they were made from.
+		type XcmOrigin: EnsureOrigin<Self::Origin, Success = MultiLocation>;
+
+		/// Converts a sibling chain, or one of its users, to the account paying for its deals.
+		type LocationToAccountId: Convert<MultiLocation, Self::AccountId>;
+
+		/// The means of sending the responses to deals requested over XCM.
+		type XcmRouter: SendXcm;
+
+		/// The location of the chain, to tell other chains where their deals are.
+		type LocationInverter: InvertLocation;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The id the next deal gets.
+	#[pallet::storage]
+	#[pallet::getter(fn next_deal_id)]
+	pub type NextDealId<T> = StorageValue<_, DealId, ValueQuery>;
+
+	/// The active deals.
+	#[pallet::storage]
+	#[pallet::getter(fn deals)]
+	pub type Deals<T: Config> = StorageMap<_, Twox64Concat, DealId, DealOf<T>, OptionQuery>;
+
+	/// The capacity, in bytes, every provider has committed to deals.
+	#[pallet::storage]
+	#[pallet::getter(fn used_capacity)]
+	pub type UsedCapacity<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u64, ValueQuery>;
+
+	/// The capacity, in bytes, committed to deals over all providers.
+	#[pallet::storage]
+	#[pallet::getter(fn total_used_capacity)]
+	pub type TotalUsedCapacity<T> = StorageValue<_, u64, ValueQuery>;
+
+	/// The totals over the deals that have not ended yet.
+	#[pallet::storage]
+	pub type ActiveDeals<T: Config> = StorageValue<_, DealTotals<BalanceOf<T>>, ValueQuery>;
+
+	/// The totals over the active deals ending at each block.
+	#[pallet::storage]
+	pub type DealEnds<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, DealTotals<BalanceOf<T>>, OptionQuery>;
+
+	/// The deals whose deletion was requested and not confirmed by all providers yet.
+	#[pallet::storage]
+	#[pallet::getter(fn deletions)]
+	pub type Deletions<T: Config> = StorageMap<_, Twox64Concat, DealId, DeletionOf<T>, OptionQuery>;
+
+	/// The lowest price per block and GiB of data every provider takes for a replica, in
+	/// `Currency` or in an asset.
+	#[pallet::storage]
+	#[pallet::getter(fn asking_prices)]
+	pub type AskingPrices<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		Option<T::AssetId>,
+		BalanceOf<T>,
+		OptionQuery,
+	>;
+
+	/// The payments and refunds that could not be transferred, by recipient and by asset, or
+	/// `None` for `Currency`. They stay with the pallet account until claimed.
+	#[pallet::storage]
+	#[pallet::getter(fn owed)]
+	pub type Owed<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		Option<T::AssetId>,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A deal was matched with its providers.
+		DealCreated { deal_id: DealId, owner: T::AccountId, providers: Vec<T::AccountId> },
+		/// A deal ended and the providers were paid.
+		DealSettled { deal_id: DealId },
+		/// The owner requested the deletion of a deal and was refunded the unused price.
+		DeletionRequested { deal_id: DealId, refund: BalanceOf<T>, confirm_by: T::BlockNumber },
+		/// A provider confirmed it deleted the data of a deal and was paid.
+		DeletionConfirmed { deal_id: DealId, provider: T::AccountId },
+		/// All providers confirmed the deletion of a deal.
+		DeletionCompleted { deal_id: DealId },
+		/// The deletion window passed without confirmation from `providers`. Their payment went
+		/// back to the owner.
+		DeletionOverdue { deal_id: DealId, providers: Vec<T::AccountId> },
+		/// The ownership of a deal, and the price reserved for it, moved to a new owner.
+		DealTransferred { deal_id: DealId, from: T::AccountId, to: T::AccountId },
+		/// The response to a deal requested over XCM was sent, with the id of the deal if it was
+		/// created.
+		DealResponseSent { query_id: QueryId, deal_id: Option<DealId> },
+		/// A provider set or withdrew its asking price in `Currency` or in an asset.
+		AskingPriceSet {
+			provider: T::AccountId,
+			asset: Option<T::AssetId>,
+			price: Option<BalanceOf<T>>,
+		},
+		/// A payment or refund could not be transferred and can be claimed later.
+		PaymentOwed { who: T::AccountId, asset: Option<T::AssetId>, amount: BalanceOf<T> },
+		/// Part of a payment in `Currency` was no longer reserved by the owner of the deal, for
+		/// example because another pallet slashed it, and could not be paid.
+		PaymentFailed { who: T::AccountId, amount: BalanceOf<T> },
+		/// What was owed to an account was transferred to it.
+		OwedClaimed { who: T::AccountId, asset: Option<T::AssetId>, amount: BalanceOf<T> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A deal has to store some data.
+		EmptyData,
+		/// The number of replicas is zero or above `MaxReplicas`.
+		InvalidReplicas,
+		/// The deal is shorter than `MinDuration`.
+		DurationTooShort,
+		/// The rules cannot be met by any placement: a minimum exceeds the number of replicas
+		/// or a maximum is zero.
+		InvalidRules,
+		/// A candidate was named twice.
+		DuplicateCandidate,
+		/// Fewer candidates than replicas accept deals and have enough unused proven capacity.
+		NotEnoughProviders,
+		/// The eligible candidates do not cover `min_regions` distinct regions.
+		TooFewRegions,
+		/// The eligible candidates do not cover `min_operators` distinct operators.
+		TooFewOperators,
+		/// Too few eligible candidates are left once `max_per_region` is applied.
+		TooManyInRegion,
+		/// Too few eligible candidates are left once `max_per_operator` is applied.
+		TooManyPerOperator,
+		/// The deal does not exist.
+		UnknownDeal,
+		/// The deal has not ended yet.
+		DealNotEnded,
+		/// Only the owner of a deal can request its deletion or transfer it.
+		NotOwner,
+		/// The deal has ended already and has to be settled instead.
+		DealEnded,
+		/// No deletion was requested for the deal.
+		UnknownDeletion,
+		/// The caller does not hold the data of the deal or confirmed its deletion already.
+		NotPending,
+		/// The deletion window has passed.
+		DeletionWindowPassed,
+		/// The deletion window has not passed yet.
+		DeletionWindowOpen,
+		/// The call was not made over XCM by a sibling chain or one of its users.
+		NotFromSibling,
+		/// The response could not be sent to the requesting chain.
+		ResponseFailed,
+		/// The deal is longer than `MaxDuration`.
+		DurationTooLong,
+		/// Every diversity rule can be met on its own by the eligible candidates, but no
+		/// placement meets all of them.
+		ConflictingRules,
+		/// No placement was found within the search limit. Naming fewer candidates or relaxing
+		/// the rules helps.
+		PlacementTooComplex,
+		/// Only storage providers can set asking prices.
+		NotProvider,
+		/// Part of the price of the deal is no longer reserved.
+		PriceNotReserved,
+		/// Nothing is owed to the caller in this asset.
+		NothingOwed,
+	}
+
+	impl<T> From<PlacementError> for Error<T> {
+		fn from(error: PlacementError) -> Self {
+			match error {
+				PlacementError::NotEnoughProviders => Error::<T>::NotEnoughProviders,
+				PlacementError::TooFewRegions => Error::<T>::TooFewRegions,
+				PlacementError::TooFewOperators => Error::<T>::TooFewOperators,
+				PlacementError::TooManyInRegion => Error::<T>::TooManyInRegion,
+				PlacementError::TooManyPerOperator => Error::<T>::TooManyPerOperator,
+				PlacementError::ConflictingRules => Error::<T>::ConflictingRules,
+				PlacementError::TooComplex => Error::<T>::PlacementTooComplex,
+			}
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			if let Some(ended) = DealEnds::<T>::take(now) {
+				ActiveDeals::<T>::mutate(|active| active.remove(ended));
+			}
+			T::DbWeight::get().reads_writes(2, 2)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Store `size` bytes of data committed to by `data_root` with `replicas` providers
+		/// picked among `candidates`, for `duration` blocks. Only candidates asking at most
+		/// `price_per_block` are picked and, with `verified_only`, only candidates with a verified
+		/// identity.
+		///
+		/// The full price of `price_per_block` for every replica and block is reserved from the
+		/// caller until the deal is settled. With an `asset`, the price is in that asset and is
+		/// held by the pallet account instead.
+		#[pallet::weight(
+			50_000 + 20_000 * placement::MAX_STEPS as Weight + T::DbWeight::get().reads_writes(
+				5 + 4 * T::MaxCandidates::get() as u64,
+				6 + T::MaxReplicas::get() as u64,
+			)
+		)]
+		#[allow(clippy::too_many_arguments)]
+		pub fn create_deal(
+			origin: OriginFor<T>,
+			data_root: H256,
+			size: u64,
+			duration: T::BlockNumber,
+			price_per_block: BalanceOf<T>,
+			asset: Option<T::AssetId>,
+			replicas: u32,
+			rules: DiversityRules,
+			verified_only: bool,
+			candidates: BoundedVec<T::AccountId, T::MaxCandidates>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			Self::do_create_deal(
+				owner,
+				data_root,
+				size,
+				duration,
+				price_per_block,
+				asset,
+				replicas,
+				rules,
+				verified_only,
+				candidates,
+			)?;
+			Ok(())
+		}
+
+		/// Create a deal like [`Pallet::create_deal`] on behalf of a sibling chain, and send the
+		/// result back to the chain as the response to its query `query_id`.
+		///
+		/// Meant to be called over XCM with a `Transact` of origin kind `Xcm`, by the chain or by
+		/// one of its users. The deal is paid for by the account `LocationToAccountId` gives the
+		/// origin. A created deal is reported as the non-fungible asset of this pallet whose
+		/// instance is the deal id. A deal that could not be created is reported as an execution
+		/// error, see [`Pallet::xcm_error`].
+		#[pallet::weight(
+			100_000 + 20_000 * placement::MAX_STEPS as Weight + T::DbWeight::get().reads_writes(
+				7 + 4 * T::MaxCandidates::get() as u64,
+				7 + T::MaxReplicas::get() as u64,
+			)
+		)]
+		#[allow(clippy::too_many_arguments)]
+		pub fn create_deal_with_response(
+			origin: OriginFor<T>,
+			query_id: QueryId,
+			max_response_weight: Weight,
+			data_root: H256,
+			size: u64,
+			duration: T::BlockNumber,
+			price_per_block: BalanceOf<T>,
+			asset: Option<T::AssetId>,
+			replicas: u32,
+			rules: DiversityRules,
+			verified_only: bool,
+			candidates: BoundedVec<T::AccountId, T::MaxCandidates>,
+		) -> DispatchResult {
+			let location = T::XcmOrigin::ensure_origin(origin)?;
+			let respond_to = match (location.parents, location.first_interior()) {
+				(1, Some(Parachain(id))) => MultiLocation::new(1, X1(Parachain(*id))),
+				_ => return Err(Error::<T>::NotFromSibling.into()),
+			};
+			let owner = T::LocationToAccountId::convert(location)
+				.map_err(|_| Error::<T>::NotFromSibling)?;
+
+			// A failed deal must not be rolled back with its response, so it has its own layer.
+			let deal_id = with_storage_layer(|| {
+				Self::do_create_deal(
+					owner,
+					data_root,
+					size,
+					duration,
+					price_per_block,
+					asset,
+					replicas,
+					rules,
+					verified_only,
+					candidates,
+				)
+			});
+			let response = match deal_id {
+				Ok(deal_id) => Response::Assets(Self::deal_asset(deal_id, &respond_to)?.into()),
+				Err(error) => Response::ExecutionResult(Some((0, Self::xcm_error(error)))),
+			};
+
+			let message =
+				Xcm(vec![QueryResponse { query_id, response, max_weight: max_response_weight }]);
+			T::XcmRouter::send_xcm(respond_to, message).map_err(|_| Error::<T>::ResponseFailed)?;
+
+			Self::deposit_event(Event::DealResponseSent { query_id, deal_id: deal_id.ok() });
+			Ok(())
+		}
+
+		/// Pay the providers of an ended deal and release their capacity.
+		///
+		/// Can be called by anyone once the deal has ended. Every provider is paid on its own, a
+		/// payment that fails is owed to the provider and the deal is settled regardless.
+		#[pallet::weight(
+			10_000 + T::DbWeight::get().reads_writes(
+				2 + 4 * T::MaxReplicas::get() as u64,
+				2 + 4 * T::MaxReplicas::get() as u64,
+			)
+		)]
+		pub fn settle_deal(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let deal = Deals::<T>::get(deal_id).ok_or(Error::<T>::UnknownDeal)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(now >= deal.end, Error::<T>::DealNotEnded);
+
+			let payment = Self::payment(&deal);
+			for provider in deal.providers.iter() {
+				Self::pay(&deal, provider, payment);
+				Self::release(provider, deal.size);
+			}
+			Deals::<T>::remove(deal_id);
+
+			Self::deposit_event(Event::DealSettled { deal_id });
+			Ok(())
+		}
+
+		/// End a deal early and request its providers to delete the data.
+		///
+		/// The price of the remaining blocks is refunded. The price of the blocks served stays
+		/// reserved until the providers confirm the deletion.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 5))]
+		pub fn request_deletion(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut deal = Deals::<T>::get(deal_id).ok_or(Error::<T>::UnknownDeal)?;
+			ensure!(deal.owner == who, Error::<T>::NotOwner);
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(now < deal.end, Error::<T>::DealEnded);
+
+			let remaining = deal.end.saturating_sub(now).saturated_into::<u128>();
+			let refund = deal
+				.price_per_block
+				.saturating_mul(BalanceOf::<T>::saturated_from(remaining))
+				.saturating_mul((deal.providers.len() as u32).into());
+			Self::refund(&deal, refund);
+			Self::end_early(&deal);
+
+			deal.end = now;
+			let pending = deal.providers.clone();
+			Deals::<T>::remove(deal_id);
+			Deletions::<T>::insert(deal_id, DeletionRequest { deal, requested_at: now, pending });
+
+			let confirm_by = now.saturating_add(T::DeletionWindow::get());
+			Self::deposit_event(Event::DeletionRequested { deal_id, refund, confirm_by });
+			Ok(())
+		}
+
+		/// Confirm that the caller deleted the data of a deal and collect the payment for the
+		/// blocks it served.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(7, 7))]
+		pub fn confirm_deletion(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut request = Deletions::<T>::get(deal_id).ok_or(Error::<T>::UnknownDeletion)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				now <= request.requested_at.saturating_add(T::DeletionWindow::get()),
+				Error::<T>::DeletionWindowPassed
+			);
+			let index = request
+				.pending
+				.iter()
+				.position(|provider| provider == &who)
+				.ok_or(Error::<T>::NotPending)?;
+			request.pending.remove(index);
+
+			Self::pay(&request.deal, &who, Self::payment(&request.deal));
+			Self::release(&who, request.deal.size);
+			Self::deposit_event(Event::DeletionConfirmed { deal_id, provider: who });
+
+			if request.pending.is_empty() {
+				Deletions::<T>::remove(deal_id);
+				Self::deposit_event(Event::DeletionCompleted { deal_id });
+			} else {
+				Deletions::<T>::insert(deal_id, request);
+			}
+			Ok(())
+		}
+
+		/// Transfer the ownership of a deal that has not been settled, or of its pending deletion,
+		/// to `new_owner`, along with the price reserved for it.
+		///
+		/// With `pallet_recovery`, the rescuer of a lost account calls this as the lost account.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3))]
+		pub fn transfer_deal(
+			origin: OriginFor<T>,
+			deal_id: DealId,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			if let Some(mut deal) = Deals::<T>::get(deal_id) {
+				ensure!(deal.owner == who, Error::<T>::NotOwner);
+				Self::transfer_reserved(&deal, &new_owner, deal.providers.len())?;
+				deal.owner = new_owner.clone();
+				Deals::<T>::insert(deal_id, deal);
+			} else {
+				let mut request = Deletions::<T>::get(deal_id).ok_or(Error::<T>::UnknownDeal)?;
+				ensure!(request.deal.owner == who, Error::<T>::NotOwner);
+				// Only the providers yet to confirm are still to be paid.
+				Self::transfer_reserved(&request.deal, &new_owner, request.pending.len())?;
+				request.deal.owner = new_owner.clone();
+				Deletions::<T>::insert(deal_id, request);
+			}
+
+			Self::deposit_event(Event::DealTransferred { deal_id, from: who, to: new_owner });
+			Ok(())
+		}
+
+		/// Close a deletion request whose window has passed. The payment of the providers that
+		/// did not confirm goes back to the owner.
+		///
+		/// Can be called by anyone.
+		#[pallet::weight(
+			10_000 + T::DbWeight::get().reads_writes(
+				3 + T::MaxReplicas::get() as u64,
+				3 + T::MaxReplicas::get() as u64,
+			)
+		)]
+		pub fn close_deletion(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let request = Deletions::<T>::get(deal_id).ok_or(Error::<T>::UnknownDeletion)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				now > request.requested_at.saturating_add(T::DeletionWindow::get()),
+				Error::<T>::DeletionWindowOpen
+			);
+
+			let payment = Self::payment(&request.deal);
+			for provider in request.pending.iter() {
+				Self::refund(&request.deal, payment);
+				Self::release(provider, request.deal.size);
+			}
+			Deletions::<T>::remove(deal_id);
+
+			Self::deposit_event(Event::DeletionOverdue {
+				deal_id,
+				providers: request.pending.into_inner(),
+			});
+			Ok(())
+		}
+
+		/// Take deals paid in `asset`, or in `Currency` without one, for at least `price` per
+		/// block and GiB of data, or stop taking them with `None`.
+		///
+		/// Deals that were already created are not affected.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_asking_price(
+			origin: OriginFor<T>,
+			asset: Option<T::AssetId>,
+			price: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			match price {
+				Some(price) => {
+					ensure!(T::Providers::profile(&who).is_some(), Error::<T>::NotProvider);
+					AskingPrices::<T>::insert(&who, asset, price);
+				},
+				None => AskingPrices::<T>::remove(&who, asset),
+			}
+
+			Self::deposit_event(Event::AskingPriceSet { provider: who, asset, price });
+			Ok(())
+		}
+
+		/// Transfer to the caller what it is owed in `asset`, or in `Currency` with `None`, from
+		/// payments or refunds that failed.
+		#[pallet::weight(50_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn claim_owed(origin: OriginFor<T>, asset: Option<T::AssetId>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let amount = Owed::<T>::take(&who, asset);
+			ensure!(!amount.is_zero(), Error::<T>::NothingOwed);
+			match asset {
+				None => T::Currency::transfer(
+					&Self::account_id(),
+					&who,
+					amount,
+					ExistenceRequirement::AllowDeath,
+				)?,
+				Some(asset) => {
+					T::Assets::transfer(asset, &Self::account_id(), &who, amount, false)?;
+				},
+			}
+
+			Self::deposit_event(Event::OwedClaimed { who, asset, amount });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The proven capacity, in bytes, of `who` that is not committed to deals yet.
+		pub fn free_capacity(who: &T::AccountId) -> u64 {
+			T::Providers::proven_capacity(who).saturating_sub(UsedCapacity::<T>::get(who))
+		}
+
+		/// Whether `who` can be matched with a deal of `size` bytes: it accepts deals, has enough
+		/// unused proven capacity and, with `verified_only`, a verified identity.
+		pub fn is_eligible(who: &T::AccountId, size: u64, verified_only: bool) -> bool {
+			T::Providers::accepts_deals(who) &&
+				Self::free_capacity(who) >= size &&
+				(!verified_only || T::Identities::is_verified(who))
+		}
+
+		/// Whether `who` takes a replica of `size` bytes paid `price_per_block` in `asset`.
+		pub fn accepts_price(
+			who: &T::AccountId,
+			asset: &Option<T::AssetId>,
+			size: u64,
+			price_per_block: BalanceOf<T>,
+		) -> bool {
+			AskingPrices::<T>::get(who, asset).map_or(false, |ask| {
+				let asked = ask.saturated_into::<u128>().saturating_mul(size.into());
+				let offered = price_per_block.saturated_into::<u128>().saturating_mul(GIB.into());
+				asked <= offered
+			})
+		}
+
+		/// The number of deals that have not ended yet and the average price per replica and
+		/// block of those paid in `Currency`.
+		pub fn active_deals() -> (u32, BalanceOf<T>) {
+			let active = ActiveDeals::<T>::get();
+			if active.priced == 0 {
+				return (active.deals, Zero::zero())
+			}
+			(active.deals, active.price / active.priced.into())
+		}
+
+		/// Whether the data of a deal may still be served: the deal has not ended, either at the
+		/// end of its duration or by a deletion request.
+		pub fn is_servable(deal_id: DealId) -> bool {
+			let now = frame_system::Pallet::<T>::block_number();
+			Deals::<T>::get(deal_id).map_or(false, |deal| now < deal.end)
+		}
+
+		/// Match a deal of `owner` with its providers and set its price aside.
+		#[allow(clippy::too_many_arguments)]
+		fn do_create_deal(
+			owner: T::AccountId,
+			data_root: H256,
+			size: u64,
+			duration: T::BlockNumber,
+			price_per_block: BalanceOf<T>,
+			asset: Option<T::AssetId>,
+			replicas: u32,
+			rules: DiversityRules,
+			verified_only: bool,
+			candidates: BoundedVec<T::AccountId, T::MaxCandidates>,
+		) -> Result<DealId, DispatchError> {
+			ensure!(size > 0, Error::<T>::EmptyData);
+			ensure!(replicas > 0 && replicas <= T::MaxReplicas::get(), Error::<T>::InvalidReplicas);
+			ensure!(duration >= T::MinDuration::get(), Error::<T>::DurationTooShort);
+			ensure!(duration <= T::MaxDuration::get(), Error::<T>::DurationTooLong);
+			ensure!(
+				rules.min_regions <= replicas &&
+					rules.min_operators <= replicas &&
+					rules.max_per_region != Some(0) &&
+					rules.max_per_operator != Some(0),
+				Error::<T>::InvalidRules
+			);
+
+			let mut seen = BTreeSet::new();
+			ensure!(candidates.iter().all(|who| seen.insert(who)), Error::<T>::DuplicateCandidate);
+
+			let eligible = candidates
+				.iter()
+				.filter(|who| Self::is_eligible(who, size, verified_only))
+				.filter(|who| Self::accepts_price(who, &asset, size, price_per_block))
+				.filter_map(|who| {
+					T::Providers::profile(who).map(|(region, operator)| Candidate {
+						who: who.clone(),
+						region,
+						operator,
+					})
+				})
+				.collect::<Vec<_>>();
+			let providers =
+				placement::place(&eligible, replicas, &rules).map_err(Error::<T>::from)?;
+
+			let blocks = BalanceOf::<T>::saturated_from(duration.saturated_into::<u128>());
+			let total = price_per_block
+				.checked_mul(&blocks)
+				.and_then(|price| price.checked_mul(&replicas.into()))
+				.ok_or(ArithmeticError::Overflow)?;
+			Self::hold(&owner, &asset, total)?;
+
+			for provider in &providers {
+				UsedCapacity::<T>::mutate(provider, |used| *used = used.saturating_add(size));
+			}
+			TotalUsedCapacity::<T>::mutate(|total| {
+				*total = total.saturating_add(size.saturating_mul(replicas.into()))
+			});
+
+			let deal_id = NextDealId::<T>::get();
+			NextDealId::<T>::put(deal_id.saturating_add(1));
+
+			let start = frame_system::Pallet::<T>::block_number();
+			let end = start.saturating_add(duration);
+			ActiveDeals::<T>::mutate(|active| active.add(price_per_block, asset.is_none()));
+			DealEnds::<T>::mutate(end, |ending| {
+				ending
+					.get_or_insert_with(Default::default)
+					.add(price_per_block, asset.is_none())
+			});
+			Deals::<T>::insert(
+				deal_id,
+				Deal {
+					owner: owner.clone(),
+					data_root,
+					size,
+					providers: providers
+						.clone()
+						.try_into()
+						.expect("placement picks exactly `replicas` providers; qed"),
+					price_per_block,
+					asset,
+					start,
+					end,
+				},
+			);
+
+			Self::deposit_event(Event::DealCreated { deal_id, owner, providers });
+			Ok(deal_id)
+		}
+
+		/// The deal `deal_id` as a non-fungible asset of this pallet, as seen from `dest`.
+		fn deal_asset(deal_id: DealId, dest: &MultiLocation) -> Result<MultiAsset, DispatchError> {
+			let pallet = MultiLocation::new(0, X1(PalletInstance(Self::index() as u8)));
+			let deal = AssetInstance::Index(deal_id.into());
+			MultiAsset { id: Concrete(pallet), fun: NonFungible(deal) }
+				.reanchored(dest, &T::LocationInverter::ancestry())
+				.map_err(|()| Error::<T>::ResponseFailed.into())
+		}
+
+		/// The execution error reporting why a deal requested over XCM could not be created.
+		///
+		/// Errors of a pallet, such as this one or the balances pallet when the price cannot be
+		/// reserved, are reported as `Trap` with the index of the pallet in the second byte and
+		/// the index of the error in the first. Errors of tokens, arithmetic and origins have
+		/// execution errors of their own.
+		pub fn xcm_error(error: DispatchError) -> XcmError {
+			match error {
+				DispatchError::Module(ModuleError { index, error, .. }) =>
+					XcmError::Trap(u64::from(index) << 8 | u64::from(error[0])),
+				DispatchError::Token(error) => XcmError::FailedToTransactAsset(error.into()),
+				DispatchError::Arithmetic(_) => XcmError::Overflow,
+				DispatchError::BadOrigin => XcmError::BadOrigin,
+				error => XcmError::FailedToTransactAsset(error.into()),
+			}
+		}
+
+		/// The payment every provider of `deal` receives for the blocks from its start to its end.
+		fn payment(deal: &DealOf<T>) -> BalanceOf<T> {
+			let blocks = deal.end.saturating_sub(deal.start).saturated_into::<u128>();
+			deal.price_per_block.saturating_mul(BalanceOf::<T>::saturated_from(blocks))
+		}
+
+		/// Move the price still reserved for `replicas` replicas of `deal` from its owner to
+		/// `new_owner`.
+		fn transfer_reserved(
+			deal: &DealOf<T>,
+			new_owner: &T::AccountId,
+			replicas: usize,
+		) -> DispatchResult {
+			// The price of deals paid in assets stays with the pallet account.
+			if deal.asset.is_none() {
+				let reserved = Self::payment(deal).saturating_mul((replicas as u32).into());
+				Self::repatriate(&deal.owner, new_owner, reserved, BalanceStatus::Reserved)?;
+			}
+			Ok(())
+		}
+
+		/// The account holding the price of deals paid in assets.
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// The reserve holding the price of deals paid in `Currency`.
+		fn reserve_id() -> [u8; 8] {
+			T::PalletId::get().0
+		}
+
+		/// Set the price `amount` of a deal paid in `asset` aside from `owner`.
+		fn hold(
+			owner: &T::AccountId,
+			asset: &Option<T::AssetId>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			match asset {
+				None => T::Currency::reserve_named(&Self::reserve_id(), owner, amount),
+				Some(asset) => {
+					T::Assets::transfer(*asset, owner, &Self::account_id(), amount, false)?;
+					Ok(())
+				},
+			}
+		}
+
+		/// Pay `amount` of the price set aside for `deal` to `provider`.
+		///
+		/// A payment in `Currency` that cannot be repatriated to `provider` is moved to the pallet
+		/// account and owed to `provider`, so that one provider cannot block a settlement. What is
+		/// no longer reserved by the owner cannot be paid and is reported.
+		fn pay(deal: &DealOf<T>, provider: &T::AccountId, amount: BalanceOf<T>) {
+			if let Some(asset) = deal.asset {
+				return Self::transfer_or_owe(asset, provider, amount)
+			}
+
+			let id = Self::reserve_id();
+			let unpaid = T::Currency::repatriate_reserved_named(
+				&id,
+				&deal.owner,
+				provider,
+				amount,
+				BalanceStatus::Free,
+			)
+			.unwrap_or(amount);
+			if unpaid.is_zero() {
+				return
+			}
+
+			let missing = T::Currency::repatriate_reserved_named(
+				&id,
+				&deal.owner,
+				&Self::account_id(),
+				unpaid,
+				BalanceStatus::Free,
+			)
+			.unwrap_or(unpaid);
+			Self::owe(provider, None, unpaid.saturating_sub(missing));
+			if !missing.is_zero() {
+				// Nothing can be paid out of what is left, if anything, so it goes back to the
+				// owner rather than staying reserved for a deal that no longer exists.
+				T::Currency::unreserve_named(&id, &deal.owner, missing);
+				Self::deposit_event(Event::PaymentFailed {
+					who: provider.clone(),
+					amount: missing,
+				});
+			}
+		}
+
+		/// Give `amount` of the price set aside for `deal` back to its owner. What the owner no
+		/// longer has reserved for the deal is not refunded.
+		fn refund(deal: &DealOf<T>, amount: BalanceOf<T>) {
+			match deal.asset {
+				None => {
+					T::Currency::unreserve_named(&Self::reserve_id(), &deal.owner, amount);
+				},
+				Some(asset) => Self::transfer_or_owe(asset, &deal.owner, amount),
+			}
+		}
+
+		/// Transfer `amount` of `asset` from the pallet account to `to`, or owe it to `to` if the
+		/// transfer fails, so that one recipient cannot block a settlement.
+		fn transfer_or_owe(asset: T::AssetId, to: &T::AccountId, amount: BalanceOf<T>) {
+			if T::Assets::transfer(asset, &Self::account_id(), to, amount, false).is_err() {
+				Self::owe(to, Some(asset), amount);
+			}
+		}
+
+		/// Owe `amount` of `asset`, or of `Currency` with `None`, held by the pallet account to
+		/// `to`.
+		fn owe(to: &T::AccountId, asset: Option<T::AssetId>, amount: BalanceOf<T>) {
+			if amount.is_zero() {
+				return
+			}
+			Owed::<T>::mutate(to, asset, |owed| *owed = owed.saturating_add(amount));
+			Self::deposit_event(Event::PaymentOwed { who: to.clone(), asset, amount });
+		}
+
+		/// Move `amount` of the price reserved by `from` to `to`, into its free balance or into
+		/// its reserve for deals.
+		fn repatriate(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			amount: BalanceOf<T>,
+			status: BalanceStatus,
+		) -> DispatchResult {
+			let missing = T::Currency::repatriate_reserved_named(
+				&Self::reserve_id(),
+				from,
+				to,
+				amount,
+				status,
+			)?;
+			ensure!(missing.is_zero(), Error::<T>::PriceNotReserved);
+			Ok(())
+		}
+
+		/// Release `size` bytes of the capacity `provider` committed to deals.
+		fn release(provider: &T::AccountId, size: u64) {
+			UsedCapacity::<T>::mutate_exists(provider, |used| {
+				let released = used.map_or(0, |used| used.min(size));
+				TotalUsedCapacity::<T>::mutate(|total| *total = total.saturating_sub(released));
+				*used = used.map(|used| used.saturating_sub(size)).filter(|used| *used > 0);
+			});
+		}
+
+		/// Stop counting `deal` as active before the block it was meant to end at.
+		fn end_early(deal: &DealOf<T>) {
+			let mut ended = DealTotals::default();
+			ended.add(deal.price_per_block, deal.asset.is_none());
+			ActiveDeals::<T>::mutate(|active| active.remove(ended.clone()));
+			DealEnds::<T>::mutate_exists(deal.end, |ending| {
+				if let Some(totals) = ending {
+					totals.remove(ended);
+				}
+				if ending.as_ref().map_or(false, |totals| totals.deals == 0) {
+					*ending = None;
+				}
+			});
+		}
+	}
+}
@@ -0,0 +1,327 @@
+use crate as pallet_storage_deals;
+use frame_support::{
+	assert_ok, parameter_types,
+	traits::{ConstU32, ConstU64, Everything, Nothing},
+	weights::Weight,
+	PalletId,
+};
+use frame_system as system;
+use kumandra_primitive::storage::{StorageProviders, VerifiedIdentities};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::{
+	cell::RefCell,
+	collections::{BTreeMap, BTreeSet},
+};
+use xcm::latest::prelude::*;
+use xcm_builder::{
+	AllowTopLevelPaidExecutionFrom, CurrencyAdapter, EnsureXcmOrigin, FixedRateOfFungible,
+	FixedWeightBounds, IsConcrete, LocationInverter, SovereignSignedViaLocation, TakeWeightCredit,
+};
+use xcm_executor::{traits::Convert, XcmExecutor};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
+		XcmPallet: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
+		StorageDeals: pallet_storage_deals::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ConstU32<5>;
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = system::EnsureRoot<u64>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type ApprovalDeposit = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+/// The asset deals can be paid in besides `Balances`.
+pub const STABLECOIN: u32 = 7;
+
+/// A storage provider known to [`MockProviders`].
+#[derive(Clone)]
+pub struct MockProvider {
+	pub capacity: u64,
+	pub region: Vec<u8>,
+	pub operator: u64,
+}
+
+thread_local! {
+	static PROVIDERS: RefCell<BTreeMap<u64, MockProvider>> = RefCell::new(BTreeMap::new());
+	static VERIFIED: RefCell<BTreeSet<u64>> = RefCell::new(BTreeSet::new());
+}
+
+/// Add a provider accepting deals with `capacity` bytes of proven capacity, at any price in
+/// `Balances`.
+pub fn add_provider(who: u64, capacity: u64, region: &[u8], operator: u64) {
+	let provider = MockProvider { capacity, region: region.to_vec(), operator };
+	PROVIDERS.with(|providers| providers.borrow_mut().insert(who, provider));
+	assert_ok!(StorageDeals::set_asking_price(Origin::signed(who), None, Some(0)));
+}
+
+pub struct MockProviders;
+impl StorageProviders<u64> for MockProviders {
+	fn accepts_deals(who: &u64) -> bool {
+		PROVIDERS.with(|providers| providers.borrow().contains_key(who))
+	}
+
+	fn proven_capacity(who: &u64) -> u64 {
+		PROVIDERS.with(|providers| providers.borrow().get(who).map_or(0, |p| p.capacity))
+	}
+
+	fn profile(who: &u64) -> Option<(Vec<u8>, u64)> {
+		PROVIDERS
+			.with(|providers| providers.borrow().get(who).map(|p| (p.region.clone(), p.operator)))
+	}
+}
+
+/// Mark the identity of `who` as verified.
+pub fn verify(who: u64) {
+	VERIFIED.with(|verified| verified.borrow_mut().insert(who));
+}
+
+pub struct MockIdentities;
+impl VerifiedIdentities<u64> for MockIdentities {
+	fn is_verified(who: &u64) -> bool {
+		VERIFIED.with(|verified| verified.borrow().contains(who))
+	}
+}
+
+parameter_types! {
+	pub const MaxReplicas: u32 = 5;
+	pub const MaxCandidates: u32 = 10;
+	pub const MinDuration: u64 = 10;
+	pub const MaxDuration: u64 = 100;
+	pub const DeletionWindow: u64 = 5;
+	pub const StorageDealsPalletId: PalletId = PalletId(*b"py/deals");
+}
+
+/// The parachain id of the mock chain.
+pub const PARA_ID: u32 = 2000;
+
+/// The parachain id of the sibling buying storage.
+pub const SIBLING_ID: u32 = 2001;
+
+/// The parachain id of a sibling no message can be sent to.
+pub const UNREACHABLE_ID: u32 = 2002;
+
+/// The sovereign account of a sibling chain.
+pub fn sovereign_account(para_id: u32) -> u64 {
+	1_000 + para_id as u64
+}
+
+/// The account derived from the user `index` of a sibling chain.
+pub fn derived_account(para_id: u32, index: u64) -> u64 {
+	1_000_000 * para_id as u64 + index
+}
+
+/// Converts sibling chains to their sovereign accounts and their users to derived accounts, like
+/// `SiblingParachainConvertsVia` and `Account32Hash` do in the runtime.
+pub struct LocationToAccountId;
+impl Convert<MultiLocation, u64> for LocationToAccountId {
+	fn convert(location: MultiLocation) -> Result<u64, MultiLocation> {
+		match location {
+			MultiLocation { parents: 1, interior: X1(Parachain(para_id)) } =>
+				Ok(sovereign_account(para_id)),
+			MultiLocation {
+				parents: 1,
+				interior: X2(Parachain(para_id), AccountIndex64 { index, .. }),
+			} => Ok(derived_account(para_id, index)),
+			_ => Err(location),
+		}
+	}
+}
+
+parameter_types! {
+	pub const HereLocation: MultiLocation = MultiLocation::here();
+	pub Ancestry: MultiLocation = Parachain(PARA_ID).into();
+	pub const UnitWeightCost: Weight = 10;
+	pub const MaxInstructions: u32 = 100;
+	pub KmdPerSecond: (AssetId, u128) = (Concrete(MultiLocation::here()), 1_000_000_000);
+}
+
+thread_local! {
+	static SENT_XCM: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+}
+
+/// The messages sent to other chains.
+pub fn sent_xcm() -> Vec<(MultiLocation, Xcm<()>)> {
+	SENT_XCM.with(|sent| sent.borrow().clone())
+}
+
+/// Records the messages sent to other chains. Only the relay chain and siblings other than
+/// `UNREACHABLE_ID` are reachable.
+pub struct TestSendXcm;
+impl SendXcm for TestSendXcm {
+	fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+		let dest = dest.into();
+		if dest.parents != 1 || dest.first_interior() == Some(&Parachain(UNREACHABLE_ID)) {
+			return Err(SendError::Unroutable)
+		}
+		SENT_XCM.with(|sent| sent.borrow_mut().push((dest, message)));
+		Ok(())
+	}
+}
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type Call = Call;
+	type XcmSender = TestSendXcm;
+	type AssetTransactor =
+		CurrencyAdapter<Balances, IsConcrete<HereLocation>, LocationToAccountId, u64, ()>;
+	type OriginConverter = (
+		SovereignSignedViaLocation<LocationToAccountId, Origin>,
+		pallet_xcm::XcmPassthrough<Origin>,
+	);
+	type IsReserve = ();
+	type IsTeleporter = ();
+	type LocationInverter = LocationInverter<Ancestry>;
+	type Barrier = (TakeWeightCredit, AllowTopLevelPaidExecutionFrom<Everything>);
+	type Weigher = Weigher;
+	type Trader = FixedRateOfFungible<KmdPerSecond, ()>;
+	type ResponseHandler = ();
+	type AssetTrap = ();
+	type AssetClaims = ();
+	type SubscriptionService = ();
+}
+
+pub type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+
+impl pallet_xcm::Config for Test {
+	type Event = Event;
+	type SendXcmOrigin = EnsureXcmOrigin<Origin, ()>;
+	type XcmRouter = TestSendXcm;
+	type ExecuteXcmOrigin = EnsureXcmOrigin<Origin, ()>;
+	type XcmExecuteFilter = Nothing;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmTeleportFilter = Nothing;
+	type XcmReserveTransferFilter = Nothing;
+	type Weigher = Weigher;
+	type LocationInverter = LocationInverter<Ancestry>;
+	type Origin = Origin;
+	type Call = Call;
+
+	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
+	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+}
+
+impl pallet_storage_deals::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type AssetId = u32;
+	type Assets = Assets;
+	type PalletId = StorageDealsPalletId;
+	type Providers = MockProviders;
+	type Identities = MockIdentities;
+	type MaxReplicas = MaxReplicas;
+	type MaxCandidates = MaxCandidates;
+	type MinDuration = MinDuration;
+	type MaxDuration = MaxDuration;
+	type DeletionWindow = DeletionWindow;
+	type XcmOrigin = pallet_xcm::EnsureXcm<Everything>;
+	type LocationToAccountId = LocationToAccountId;
+	type XcmRouter = TestSendXcm;
+	type LocationInverter = LocationInverter<Ancestry>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	PROVIDERS.with(|providers| providers.borrow_mut().clear());
+	VERIFIED.with(|verified| verified.borrow_mut().clear());
+	SENT_XCM.with(|sent| sent.borrow_mut().clear());
+
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![
+			(1, 10_000),
+			(2, 100),
+			(sovereign_account(SIBLING_ID), 10_000),
+			(sovereign_account(UNREACHABLE_ID), 10_000),
+			(derived_account(SIBLING_ID, 5), 10_000),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_assets::GenesisConfig::<Test> {
+		assets: vec![(STABLECOIN, 1, true, 1)],
+		metadata: vec![],
+		accounts: vec![(STABLECOIN, 1, 1_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
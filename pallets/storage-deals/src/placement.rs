@@ -0,0 +1,213 @@
+//! Replica placement honouring the diversity rules of a deal.
+
+use crate::DiversityRules;
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	vec::Vec,
+};
+
+/// The most candidates a placement tries, which bounds its cost.
+pub const MAX_STEPS: u32 = 10_000;
+
+/// A provider eligible to hold a replica.
+pub struct Candidate<AccountId> {
+	/// The provider.
+	pub who: AccountId,
+	/// The region the provider declared.
+	pub region: Vec<u8>,
+	/// The operator the provider declared.
+	pub operator: AccountId,
+}
+
+/// The reason no placement satisfies the rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlacementError {
+	/// There are fewer eligible candidates than replicas.
+	NotEnoughProviders,
+	/// The replicas cannot be spread over `min_regions` regions.
+	TooFewRegions,
+	/// The replicas cannot be spread over `min_operators` operators.
+	TooFewOperators,
+	/// `max_per_region` leaves too few candidates.
+	TooManyInRegion,
+	/// `max_per_operator` leaves too few candidates.
+	TooManyPerOperator,
+	/// Every rule can be met on its own, but no placement meets all of them.
+	ConflictingRules,
+	/// No placement was found within `MAX_STEPS` tries.
+	TooComplex,
+}
+
+/// A partial placement, extended one candidate at a time and undone when it cannot be
+/// completed.
+struct Search<'a, AccountId> {
+	candidates: &'a [Candidate<AccountId>],
+	replicas: usize,
+	rules: &'a DiversityRules,
+	selected: Vec<usize>,
+	regions: BTreeMap<&'a [u8], u32>,
+	operators: BTreeMap<&'a AccountId, u32>,
+	steps: u32,
+}
+
+impl<'a, AccountId: Ord> Search<'a, AccountId> {
+	/// Complete the placement with candidates from `from` on. Returns whether it could be
+	/// completed, or `None` once `MAX_STEPS` candidates were tried.
+	fn complete(&mut self, from: usize) -> Option<bool> {
+		let open = self.replicas - self.selected.len();
+		if open == 0 {
+			return Some(true)
+		}
+		if !self.can_reach_minimums(from) {
+			return Some(false)
+		}
+
+		for index in from..self.candidates.len().saturating_sub(open - 1) {
+			self.steps += 1;
+			if self.steps > MAX_STEPS {
+				return None
+			}
+
+			let candidate = &self.candidates[index];
+			if !self.fits(candidate, open - 1) {
+				continue
+			}
+
+			self.select(index);
+			if self.complete(index + 1)? {
+				return Some(true)
+			}
+			self.unselect();
+		}
+		Some(false)
+	}
+
+	/// Whether `candidate` can be added without breaking a maximum, while leaving enough of the
+	/// `open` replicas after it to reach the minimums.
+	fn fits(&self, candidate: &Candidate<AccountId>, open: usize) -> bool {
+		let in_region = self.regions.get(candidate.region.as_slice()).copied().unwrap_or_default();
+		let per_operator = self.operators.get(&candidate.operator).copied().unwrap_or_default();
+		if self.rules.max_per_region.map_or(false, |max| in_region >= max) ||
+			self.rules.max_per_operator.map_or(false, |max| per_operator >= max)
+		{
+			return false
+		}
+
+		let regions = self.regions.len() + usize::from(in_region == 0);
+		let operators = self.operators.len() + usize::from(per_operator == 0);
+		(self.rules.min_regions as usize).saturating_sub(regions) <= open &&
+			(self.rules.min_operators as usize).saturating_sub(operators) <= open
+	}
+
+	/// Whether the candidates from `from` on add enough regions and operators to reach the
+	/// minimums.
+	fn can_reach_minimums(&self, from: usize) -> bool {
+		let rest = &self.candidates[from..];
+		let regions = rest
+			.iter()
+			.map(|candidate| candidate.region.as_slice())
+			.filter(|region| !self.regions.contains_key(region))
+			.collect::<BTreeSet<_>>();
+		let operators = rest
+			.iter()
+			.map(|candidate| &candidate.operator)
+			.filter(|operator| !self.operators.contains_key(operator))
+			.collect::<BTreeSet<_>>();
+		self.regions.len() + regions.len() >= self.rules.min_regions as usize &&
+			self.operators.len() + operators.len() >= self.rules.min_operators as usize
+	}
+
+	fn select(&mut self, index: usize) {
+		let candidates = self.candidates;
+		let candidate = &candidates[index];
+		*self.regions.entry(candidate.region.as_slice()).or_default() += 1;
+		*self.operators.entry(&candidate.operator).or_default() += 1;
+		self.selected.push(index);
+	}
+
+	fn unselect(&mut self) {
+		let index = self.selected.pop().expect("only called after `select`; qed");
+		let candidates = self.candidates;
+		let candidate = &candidates[index];
+		decrement(&mut self.regions, candidate.region.as_slice());
+		decrement(&mut self.operators, &candidate.operator);
+	}
+}
+
+fn decrement<K: Ord>(counts: &mut BTreeMap<K, u32>, key: K) {
+	if let Some(count) = counts.get_mut(&key) {
+		*count -= 1;
+		if *count == 0 {
+			counts.remove(&key);
+		}
+	}
+}
+
+/// Pick `replicas` providers among `candidates` so that `rules` hold.
+///
+/// Candidates are considered in the given order, so clients can list their preferred providers
+/// first: of all the placements meeting the rules, the one preferring earlier candidates is
+/// picked.
+pub fn place<AccountId: Clone + Ord>(
+	candidates: &[Candidate<AccountId>],
+	replicas: u32,
+	rules: &DiversityRules,
+) -> Result<Vec<AccountId>, PlacementError> {
+	let replicas = replicas as usize;
+	if candidates.len() < replicas {
+		return Err(PlacementError::NotEnoughProviders)
+	}
+
+	check(candidates, replicas, rules)?;
+
+	let mut search = Search {
+		candidates,
+		replicas,
+		rules,
+		selected: Vec::with_capacity(replicas),
+		regions: BTreeMap::new(),
+		operators: BTreeMap::new(),
+		steps: 0,
+	};
+	match search.complete(0) {
+		Some(true) =>
+			Ok(search.selected.iter().map(|index| candidates[*index].who.clone()).collect()),
+		Some(false) => Err(PlacementError::ConflictingRules),
+		None => Err(PlacementError::TooComplex),
+	}
+}
+
+/// Check that `candidates` can meet every rule on its own.
+fn check<AccountId: Ord>(
+	candidates: &[Candidate<AccountId>],
+	replicas: usize,
+	rules: &DiversityRules,
+) -> Result<(), PlacementError> {
+	let regions = group_sizes(candidates.iter().map(|candidate| candidate.region.as_slice()));
+	let operators = group_sizes(candidates.iter().map(|candidate| &candidate.operator));
+
+	if regions.len() < rules.min_regions as usize {
+		Err(PlacementError::TooFewRegions)
+	} else if operators.len() < rules.min_operators as usize {
+		Err(PlacementError::TooFewOperators)
+	} else if capped(&operators, rules.max_per_operator) < replicas {
+		Err(PlacementError::TooManyPerOperator)
+	} else if capped(&regions, rules.max_per_region) < replicas {
+		Err(PlacementError::TooManyInRegion)
+	} else {
+		Ok(())
+	}
+}
+
+/// The number of candidates in every group.
+fn group_sizes<K: Ord>(groups: impl Iterator<Item = K>) -> BTreeMap<K, usize> {
+	let mut sizes = BTreeMap::new();
+	groups.for_each(|group| *sizes.entry(group).or_default() += 1);
+	sizes
+}
+
+/// The most replicas the groups can hold when none holds more than `max`.
+fn capped<K>(sizes: &BTreeMap<K, usize>, max: Option<u32>) -> usize {
+	let max = max.map_or(usize::MAX, |max| max as usize);
+	sizes.values().map(|size| (*size).min(max)).sum()
+}
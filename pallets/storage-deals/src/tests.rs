@@ -0,0 +1,886 @@
+use crate::{mock::*, DiversityRules, Error, Event as StorageDealsEvent};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Hooks, PalletInfoAccess, ReservableCurrency},
+};
+use sp_core::H256;
+use sp_runtime::{
+	traits::{BadOrigin, Dispatchable},
+	ArithmeticError, DispatchResult,
+};
+use xcm::latest::prelude::*;
+use xcm_executor::XcmExecutor;
+
+fn add_providers() {
+	add_provider(10, 1_000, b"eu-west", 100);
+	add_provider(11, 1_000, b"eu-west", 100);
+	add_provider(12, 1_000, b"us-east", 101);
+	add_provider(13, 1_000, b"ap-south", 102);
+	add_provider(14, 1_000, b"us-east", 103);
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		StorageDeals::on_initialize(System::block_number());
+	}
+}
+
+fn create(replicas: u32, rules: DiversityRules, candidates: Vec<u64>) -> DispatchResult {
+	StorageDeals::create_deal(
+		Origin::signed(1),
+		H256::repeat_byte(1),
+		100,
+		20,
+		2,
+		None,
+		replicas,
+		rules,
+		false,
+		candidates.try_into().unwrap(),
+	)
+}
+
+#[test]
+fn create_deal_reserves_price_and_capacity() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11, 12]));
+
+		let deal = StorageDeals::deals(0).unwrap();
+		assert_eq!(deal.providers.into_inner(), vec![10, 11]);
+		assert_eq!((deal.start, deal.end), (1, 21));
+		assert_eq!(Balances::reserved_balance(&1), 2 * 20 * 2);
+		assert_eq!(StorageDeals::used_capacity(10), 100);
+		assert_eq!(StorageDeals::free_capacity(&10), 900);
+		assert_eq!(StorageDeals::used_capacity(12), 0);
+		assert_eq!(StorageDeals::next_deal_id(), 1);
+		System::assert_last_event(
+			StorageDealsEvent::DealCreated { deal_id: 0, owner: 1, providers: vec![10, 11] }.into(),
+		);
+	});
+}
+
+#[test]
+fn replicas_are_spread_over_regions_and_operators() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+
+		let rules = DiversityRules { min_regions: 3, ..Default::default() };
+		assert_ok!(create(3, rules, vec![10, 11, 12, 13]));
+		assert_eq!(StorageDeals::deals(0).unwrap().providers.into_inner(), vec![10, 12, 13]);
+
+		let rules = DiversityRules { min_operators: 2, ..Default::default() };
+		assert_ok!(create(2, rules, vec![10, 11, 12]));
+		assert_eq!(StorageDeals::deals(1).unwrap().providers.into_inner(), vec![10, 12]);
+
+		let rules = DiversityRules { max_per_operator: Some(1), ..Default::default() };
+		assert_ok!(create(3, rules, vec![10, 11, 12, 14]));
+		assert_eq!(StorageDeals::deals(2).unwrap().providers.into_inner(), vec![10, 12, 14]);
+	});
+}
+
+#[test]
+fn unmet_rules_are_reported() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+
+		let rules = DiversityRules { min_regions: 3, ..Default::default() };
+		assert_noop!(create(3, rules, vec![10, 11, 12, 14]), Error::<Test>::TooFewRegions);
+
+		let rules = DiversityRules { min_operators: 3, ..Default::default() };
+		assert_noop!(create(3, rules, vec![10, 11, 12]), Error::<Test>::TooFewOperators);
+
+		let rules = DiversityRules { max_per_operator: Some(1), ..Default::default() };
+		assert_noop!(create(3, rules, vec![10, 11, 12]), Error::<Test>::TooManyPerOperator);
+
+		let rules = DiversityRules { max_per_region: Some(1), ..Default::default() };
+		assert_noop!(create(3, rules, vec![10, 11, 12]), Error::<Test>::TooManyInRegion);
+
+		// Three regions and three operators are available, but the only providers in eu-west
+		// and sa-east are both run by 100.
+		add_provider(20, 1_000, b"sa-east", 100);
+		let rules =
+			DiversityRules { min_regions: 3, max_per_operator: Some(1), ..Default::default() };
+		assert_noop!(create(3, rules, vec![10, 12, 14, 20]), Error::<Test>::ConflictingRules);
+	});
+}
+
+#[test]
+fn placement_backtracks_when_an_early_pick_breaks_a_rule() {
+	new_test_ext().execute_with(|| {
+		add_provider(20, 1_000, b"eu-west", 200);
+		add_provider(21, 1_000, b"us-east", 200);
+		add_provider(22, 1_000, b"us-east", 201);
+
+		// 20 and 21 cover two regions but a single operator, 20 and 22 cover both.
+		let rules = DiversityRules { min_regions: 2, min_operators: 2, ..Default::default() };
+		assert_ok!(create(2, rules, vec![20, 21, 22]));
+		assert_eq!(StorageDeals::deals(0).unwrap().providers.into_inner(), vec![20, 22]);
+	});
+}
+
+#[test]
+fn providers_only_take_deals_at_their_asking_price() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		let gib = 1u64 << 30;
+
+		// 100 bytes at 2 per block is 2 * 2^30 / 100 per block and GiB.
+		assert_ok!(StorageDeals::set_asking_price(Origin::signed(10), None, Some(gib)));
+		assert_ok!(StorageDeals::set_asking_price(Origin::signed(11), None, Some(2 * gib / 100)));
+		System::assert_last_event(
+			StorageDealsEvent::AskingPriceSet {
+				provider: 11,
+				asset: None,
+				price: Some(2 * gib / 100),
+			}
+			.into(),
+		);
+		assert_ok!(create(1, Default::default(), vec![10, 11]));
+		assert_eq!(StorageDeals::deals(0).unwrap().providers.into_inner(), vec![11]);
+
+		// Without an asking price, a provider takes no deals.
+		assert_ok!(StorageDeals::set_asking_price(Origin::signed(12), None, None));
+		assert_noop!(
+			create(1, Default::default(), vec![10, 12]),
+			Error::<Test>::NotEnoughProviders
+		);
+		assert_noop!(
+			StorageDeals::create_deal(
+				Origin::signed(1),
+				H256::repeat_byte(1),
+				100,
+				20,
+				2,
+				Some(STABLECOIN),
+				1,
+				Default::default(),
+				false,
+				vec![13].try_into().unwrap(),
+			),
+			Error::<Test>::NotEnoughProviders
+		);
+
+		assert_noop!(
+			StorageDeals::set_asking_price(Origin::signed(99), None, Some(1)),
+			Error::<Test>::NotProvider
+		);
+	});
+}
+
+#[test]
+fn only_eligible_candidates_are_matched() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		add_provider(15, 50, b"eu-west", 104);
+
+		// 99 does not accept deals and 15 lacks the capacity.
+		assert_noop!(
+			create(2, Default::default(), vec![10, 15, 99]),
+			Error::<Test>::NotEnoughProviders
+		);
+
+		// Capacity committed to a deal is no longer free.
+		add_provider(16, 150, b"eu-west", 105);
+		assert_ok!(create(1, Default::default(), vec![16]));
+		assert_noop!(create(1, Default::default(), vec![16]), Error::<Test>::NotEnoughProviders);
+	});
+}
+
+#[test]
+fn deals_can_be_restricted_to_verified_providers() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		verify(11);
+		verify(12);
+
+		let create_verified = |candidates: Vec<u64>| {
+			StorageDeals::create_deal(
+				Origin::signed(1),
+				H256::repeat_byte(1),
+				100,
+				20,
+				2,
+				None,
+				2,
+				Default::default(),
+				true,
+				candidates.try_into().unwrap(),
+			)
+		};
+		assert_noop!(create_verified(vec![10, 11, 13]), Error::<Test>::NotEnoughProviders);
+		assert_ok!(create_verified(vec![10, 11, 12, 13]));
+		assert_eq!(StorageDeals::deals(0).unwrap().providers.into_inner(), vec![11, 12]);
+
+		assert!(StorageDeals::is_eligible(&10, 100, false));
+		assert!(!StorageDeals::is_eligible(&10, 100, true));
+		assert!(StorageDeals::is_eligible(&11, 100, true));
+		assert!(!StorageDeals::is_eligible(&11, 1_000, true));
+	});
+}
+
+#[test]
+fn invalid_deals_are_rejected() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+
+		assert_noop!(create(0, Default::default(), vec![10]), Error::<Test>::InvalidReplicas);
+		assert_noop!(
+			create(6, Default::default(), vec![10, 11, 12, 13, 14, 15]),
+			Error::<Test>::InvalidReplicas
+		);
+		assert_noop!(
+			create(2, DiversityRules { min_regions: 3, ..Default::default() }, vec![10, 12]),
+			Error::<Test>::InvalidRules
+		);
+		assert_noop!(
+			create(2, DiversityRules { max_per_region: Some(0), ..Default::default() }, vec![10]),
+			Error::<Test>::InvalidRules
+		);
+		assert_noop!(
+			create(2, Default::default(), vec![10, 10]),
+			Error::<Test>::DuplicateCandidate
+		);
+		assert_noop!(
+			StorageDeals::create_deal(
+				Origin::signed(1),
+				H256::zero(),
+				0,
+				20,
+				2,
+				None,
+				1,
+				Default::default(),
+				false,
+				vec![10].try_into().unwrap(),
+			),
+			Error::<Test>::EmptyData
+		);
+		assert_noop!(
+			StorageDeals::create_deal(
+				Origin::signed(1),
+				H256::zero(),
+				100,
+				9,
+				2,
+				None,
+				1,
+				Default::default(),
+				false,
+				vec![10].try_into().unwrap(),
+			),
+			Error::<Test>::DurationTooShort
+		);
+		assert_noop!(
+			StorageDeals::create_deal(
+				Origin::signed(1),
+				H256::zero(),
+				100,
+				101,
+				2,
+				None,
+				1,
+				Default::default(),
+				false,
+				vec![10].try_into().unwrap(),
+			),
+			Error::<Test>::DurationTooLong
+		);
+	});
+}
+
+#[test]
+fn settle_deal_pays_providers_once_ended() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+		// Reserved by another pallet, which deals must leave alone.
+		assert_ok!(Balances::reserve(&1, 50));
+
+		System::set_block_number(20);
+		assert!(StorageDeals::is_servable(0));
+		assert_noop!(StorageDeals::settle_deal(Origin::signed(2), 0), Error::<Test>::DealNotEnded);
+
+		// Ended deals are no longer served, even before they are settled.
+		System::set_block_number(21);
+		assert!(!StorageDeals::is_servable(0));
+		assert_ok!(StorageDeals::settle_deal(Origin::signed(2), 0));
+		assert_eq!(Balances::free_balance(&10), 40);
+		assert_eq!(Balances::free_balance(&11), 40);
+		assert_eq!(Balances::reserved_balance(&1), 50);
+		assert_eq!(Balances::free_balance(&1), 10_000 - 80 - 50);
+		assert_eq!(StorageDeals::used_capacity(10), 0);
+		assert!(StorageDeals::deals(0).is_none());
+		System::assert_last_event(StorageDealsEvent::DealSettled { deal_id: 0 }.into());
+
+		assert_noop!(StorageDeals::settle_deal(Origin::signed(2), 0), Error::<Test>::UnknownDeal);
+	});
+}
+
+#[test]
+fn statistics_cover_active_deals() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_eq!(StorageDeals::active_deals(), (0, 0));
+
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+		assert_ok!(StorageDeals::create_deal(
+			Origin::signed(1),
+			H256::repeat_byte(2),
+			50,
+			30,
+			4,
+			None,
+			1,
+			Default::default(),
+			false,
+			vec![12].try_into().unwrap(),
+		));
+		assert_eq!(StorageDeals::total_used_capacity(), 250);
+		assert_eq!(StorageDeals::active_deals(), (2, 3));
+
+		run_to_block(20);
+		assert_eq!(StorageDeals::active_deals(), (2, 3));
+		run_to_block(21);
+		assert_eq!(StorageDeals::active_deals(), (1, 4));
+		assert_ok!(StorageDeals::settle_deal(Origin::signed(2), 0));
+		assert_eq!(StorageDeals::total_used_capacity(), 50);
+	});
+}
+
+#[test]
+fn deletion_refunds_the_unused_price() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+		assert!(StorageDeals::is_servable(0));
+
+		System::set_block_number(6);
+		assert_noop!(StorageDeals::request_deletion(Origin::signed(2), 0), Error::<Test>::NotOwner);
+		assert_ok!(StorageDeals::request_deletion(Origin::signed(1), 0));
+
+		// 15 of the 20 blocks are refunded for both replicas.
+		assert_eq!(Balances::reserved_balance(&1), 2 * 5 * 2);
+		assert_eq!(Balances::free_balance(&1), 10_000 - 20);
+		assert!(!StorageDeals::is_servable(0));
+		assert_eq!(StorageDeals::active_deals(), (0, 0));
+		assert!(StorageDeals::deals(0).is_none());
+		assert_eq!(StorageDeals::deletions(0).unwrap().deal.end, 6);
+		System::assert_last_event(
+			StorageDealsEvent::DeletionRequested { deal_id: 0, refund: 60, confirm_by: 11 }.into(),
+		);
+
+		assert_noop!(
+			StorageDeals::request_deletion(Origin::signed(1), 0),
+			Error::<Test>::UnknownDeal
+		);
+		assert_noop!(StorageDeals::settle_deal(Origin::signed(1), 0), Error::<Test>::UnknownDeal);
+	});
+}
+
+#[test]
+fn ended_deals_cannot_be_deleted() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(1, Default::default(), vec![10]));
+
+		System::set_block_number(21);
+		assert_noop!(
+			StorageDeals::request_deletion(Origin::signed(1), 0),
+			Error::<Test>::DealEnded
+		);
+	});
+}
+
+#[test]
+fn providers_are_paid_once_they_confirm_the_deletion() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+		System::set_block_number(6);
+		assert_ok!(StorageDeals::request_deletion(Origin::signed(1), 0));
+
+		assert_noop!(
+			StorageDeals::confirm_deletion(Origin::signed(12), 0),
+			Error::<Test>::NotPending
+		);
+		assert_ok!(StorageDeals::confirm_deletion(Origin::signed(10), 0));
+		assert_eq!(Balances::free_balance(&10), 10);
+		assert_eq!(StorageDeals::used_capacity(10), 0);
+		assert_eq!(StorageDeals::used_capacity(11), 100);
+		assert_noop!(
+			StorageDeals::confirm_deletion(Origin::signed(10), 0),
+			Error::<Test>::NotPending
+		);
+
+		System::set_block_number(11);
+		assert_ok!(StorageDeals::confirm_deletion(Origin::signed(11), 0));
+		assert_eq!(Balances::free_balance(&11), 10);
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert!(StorageDeals::deletions(0).is_none());
+		System::assert_last_event(StorageDealsEvent::DeletionCompleted { deal_id: 0 }.into());
+	});
+}
+
+#[test]
+fn unconfirmed_deletions_return_the_payment_to_the_owner() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+		System::set_block_number(6);
+		assert_ok!(StorageDeals::request_deletion(Origin::signed(1), 0));
+		assert_ok!(StorageDeals::confirm_deletion(Origin::signed(10), 0));
+
+		System::set_block_number(11);
+		assert_noop!(
+			StorageDeals::close_deletion(Origin::signed(2), 0),
+			Error::<Test>::DeletionWindowOpen
+		);
+
+		System::set_block_number(12);
+		assert_noop!(
+			StorageDeals::confirm_deletion(Origin::signed(11), 0),
+			Error::<Test>::DeletionWindowPassed
+		);
+		assert_ok!(StorageDeals::close_deletion(Origin::signed(2), 0));
+		assert_eq!(Balances::free_balance(&11), 0);
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::free_balance(&1), 10_000 - 10);
+		assert_eq!(StorageDeals::used_capacity(11), 0);
+		assert!(StorageDeals::deletions(0).is_none());
+		System::assert_last_event(
+			StorageDealsEvent::DeletionOverdue { deal_id: 0, providers: vec![11] }.into(),
+		);
+	});
+}
+
+#[test]
+fn deals_can_be_transferred_with_their_reserved_price() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+
+		assert_noop!(StorageDeals::transfer_deal(Origin::signed(2), 0, 2), Error::<Test>::NotOwner);
+		assert_ok!(StorageDeals::transfer_deal(Origin::signed(1), 0, 2));
+		System::assert_last_event(
+			StorageDealsEvent::DealTransferred { deal_id: 0, from: 1, to: 2 }.into(),
+		);
+		assert_eq!(StorageDeals::deals(0).unwrap().owner, 2);
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::reserved_balance(&2), 80);
+		assert_eq!(Balances::free_balance(&2), 100);
+
+		// The new owner is refunded and pays the providers.
+		System::set_block_number(6);
+		assert_ok!(StorageDeals::request_deletion(Origin::signed(2), 0));
+		assert_eq!(Balances::free_balance(&2), 160);
+		assert_ok!(StorageDeals::confirm_deletion(Origin::signed(10), 0));
+		assert_eq!(Balances::reserved_balance(&2), 10);
+		assert_eq!(Balances::free_balance(&10), 10);
+
+		// A pending deletion moves with the payment of the providers yet to confirm.
+		assert_noop!(StorageDeals::transfer_deal(Origin::signed(1), 0, 1), Error::<Test>::NotOwner);
+		assert_ok!(StorageDeals::transfer_deal(Origin::signed(2), 0, 1));
+		assert_eq!(StorageDeals::deletions(0).unwrap().deal.owner, 1);
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::reserved_balance(&1), 10);
+		assert_ok!(StorageDeals::confirm_deletion(Origin::signed(11), 0));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::free_balance(&11), 10);
+
+		assert_noop!(
+			StorageDeals::transfer_deal(Origin::signed(1), 0, 2),
+			Error::<Test>::UnknownDeal
+		);
+	});
+}
+
+#[test]
+fn deals_can_be_paid_in_assets() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		for provider in [10, 11, 12, 13] {
+			assert_ok!(StorageDeals::set_asking_price(
+				Origin::signed(provider),
+				Some(STABLECOIN),
+				Some(0)
+			));
+		}
+		let create_in_stablecoin = |candidates: Vec<u64>| {
+			StorageDeals::create_deal(
+				Origin::signed(1),
+				H256::repeat_byte(1),
+				100,
+				20,
+				2,
+				Some(STABLECOIN),
+				2,
+				Default::default(),
+				false,
+				candidates.try_into().unwrap(),
+			)
+		};
+		let pallet_account = StorageDeals::account_id();
+
+		assert_ok!(create_in_stablecoin(vec![10, 11]));
+		assert_eq!(Assets::balance(STABLECOIN, 1), 1_000 - 80);
+		assert_eq!(Assets::balance(STABLECOIN, pallet_account), 80);
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(StorageDeals::deals(0).unwrap().asset, Some(STABLECOIN));
+		assert_eq!(StorageDeals::active_deals(), (1, 0));
+
+		// The price of a transferred deal stays with the pallet account.
+		assert_ok!(StorageDeals::transfer_deal(Origin::signed(1), 0, 2));
+		assert_eq!(Assets::balance(STABLECOIN, pallet_account), 80);
+
+		System::set_block_number(6);
+		assert_ok!(StorageDeals::request_deletion(Origin::signed(2), 0));
+		assert_eq!(Assets::balance(STABLECOIN, 2), 60);
+		assert_ok!(StorageDeals::confirm_deletion(Origin::signed(10), 0));
+		assert_eq!(Assets::balance(STABLECOIN, 10), 10);
+
+		System::set_block_number(12);
+		assert_ok!(StorageDeals::close_deletion(Origin::signed(3), 0));
+		assert_eq!(Assets::balance(STABLECOIN, 2), 70);
+		assert_eq!(Assets::balance(STABLECOIN, pallet_account), 0);
+
+		// The whole price has to be available.
+		assert_noop!(
+			StorageDeals::create_deal(
+				Origin::signed(2),
+				H256::repeat_byte(2),
+				100,
+				20,
+				2,
+				Some(STABLECOIN),
+				2,
+				Default::default(),
+				false,
+				vec![12, 13].try_into().unwrap(),
+			),
+			pallet_assets::Error::<Test>::BalanceLow
+		);
+	});
+}
+
+#[test]
+fn failed_asset_payments_are_owed() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		for provider in [10, 11] {
+			assert_ok!(StorageDeals::set_asking_price(
+				Origin::signed(provider),
+				Some(STABLECOIN),
+				Some(0)
+			));
+		}
+		assert_ok!(StorageDeals::create_deal(
+			Origin::signed(1),
+			H256::repeat_byte(1),
+			100,
+			20,
+			2,
+			Some(STABLECOIN),
+			2,
+			Default::default(),
+			false,
+			vec![10, 11].try_into().unwrap(),
+		));
+
+		// Settling does not wait for the asset to be thawed.
+		assert_ok!(Assets::freeze_asset(Origin::signed(1), STABLECOIN));
+		System::set_block_number(21);
+		assert_ok!(StorageDeals::settle_deal(Origin::signed(2), 0));
+		System::assert_has_event(
+			StorageDealsEvent::PaymentOwed { who: 11, asset: Some(STABLECOIN), amount: 40 }.into(),
+		);
+		assert_eq!(StorageDeals::owed(10, Some(STABLECOIN)), 40);
+		assert_eq!(Assets::balance(STABLECOIN, StorageDeals::account_id()), 80);
+
+		assert_noop!(
+			StorageDeals::claim_owed(Origin::signed(10), Some(STABLECOIN)),
+			pallet_assets::Error::<Test>::Frozen
+		);
+		assert_ok!(Assets::thaw_asset(Origin::signed(1), STABLECOIN));
+		assert_ok!(StorageDeals::claim_owed(Origin::signed(10), Some(STABLECOIN)));
+		System::assert_last_event(
+			StorageDealsEvent::OwedClaimed { who: 10, asset: Some(STABLECOIN), amount: 40 }.into(),
+		);
+		assert_eq!(Assets::balance(STABLECOIN, 10), 40);
+		assert_eq!(StorageDeals::owed(10, Some(STABLECOIN)), 0);
+		assert_noop!(
+			StorageDeals::claim_owed(Origin::signed(10), Some(STABLECOIN)),
+			Error::<Test>::NothingOwed
+		);
+	});
+}
+
+#[test]
+fn one_failed_payment_does_not_block_the_settlement() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+		Balances::make_free_balance_be(&StorageDeals::account_id(), 1);
+		// Paying the second provider would overflow its balance.
+		Balances::make_free_balance_be(&11, u64::MAX - 10);
+
+		System::set_block_number(21);
+		assert_ok!(StorageDeals::settle_deal(Origin::signed(2), 0));
+		assert_eq!(Balances::free_balance(&10), 40);
+		assert_eq!(Balances::free_balance(&11), u64::MAX - 10);
+		assert_eq!(Balances::free_balance(&StorageDeals::account_id()), 41);
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(StorageDeals::owed(11, None), 40);
+		System::assert_has_event(
+			StorageDealsEvent::PaymentOwed { who: 11, asset: None, amount: 40 }.into(),
+		);
+		assert_eq!(StorageDeals::used_capacity(10), 0);
+		assert_eq!(StorageDeals::used_capacity(11), 0);
+		assert!(StorageDeals::deals(0).is_none());
+		System::assert_last_event(StorageDealsEvent::DealSettled { deal_id: 0 }.into());
+
+		assert_noop!(StorageDeals::claim_owed(Origin::signed(11), None), ArithmeticError::Overflow);
+		Balances::make_free_balance_be(&11, 1);
+		assert_ok!(StorageDeals::claim_owed(Origin::signed(11), None));
+		System::assert_last_event(
+			StorageDealsEvent::OwedClaimed { who: 11, asset: None, amount: 40 }.into(),
+		);
+		assert_eq!(Balances::free_balance(&11), 41);
+		assert_eq!(Balances::free_balance(&StorageDeals::account_id()), 1);
+	});
+}
+
+#[test]
+fn payments_no_longer_reserved_are_reported() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(create(2, Default::default(), vec![10, 11]));
+		Balances::make_free_balance_be(&StorageDeals::account_id(), 1);
+		Balances::make_free_balance_be(&10, 1);
+		Balances::make_free_balance_be(&11, 1);
+		// Another pallet slashes most of the price reserved for the deal.
+		let _ = Balances::slash_reserved(&1, 50);
+
+		System::set_block_number(21);
+		assert_ok!(StorageDeals::settle_deal(Origin::signed(2), 0));
+		assert_eq!(Balances::free_balance(&10), 1 + 30);
+		assert_eq!(Balances::free_balance(&11), 1);
+		System::assert_has_event(StorageDealsEvent::PaymentFailed { who: 10, amount: 10 }.into());
+		System::assert_has_event(StorageDealsEvent::PaymentFailed { who: 11, amount: 40 }.into());
+		assert_eq!(StorageDeals::owed(10, None), 0);
+		assert_eq!(StorageDeals::used_capacity(10), 0);
+		assert_eq!(StorageDeals::used_capacity(11), 0);
+		assert!(StorageDeals::deals(0).is_none());
+		System::assert_last_event(StorageDealsEvent::DealSettled { deal_id: 0 }.into());
+	});
+}
+
+fn sibling() -> MultiLocation {
+	MultiLocation::new(1, X1(Parachain(SIBLING_ID)))
+}
+
+/// The user `index` of the sibling.
+fn sibling_user(index: u64) -> Junction {
+	AccountIndex64 { network: NetworkId::Any, index }
+}
+
+/// The deal `deal_id` as the sibling is told about it.
+fn deal_on_sibling(deal_id: u64) -> MultiAsset {
+	let pallet = PalletInstance(<StorageDeals as PalletInfoAccess>::index() as u8);
+	MultiAsset {
+		id: Concrete(MultiLocation::new(1, X2(Parachain(PARA_ID), pallet))),
+		fun: NonFungible(AssetInstance::Index(deal_id.into())),
+	}
+}
+
+/// The response to the query `query_id` of the sibling.
+fn response(query_id: u64, response: Response) -> (MultiLocation, Xcm<()>) {
+	(sibling(), Xcm(vec![QueryResponse { query_id, response, max_weight: 1_000 }]))
+}
+
+/// The call requesting 2 replicas of 100 bytes for 20 blocks at 2 per block, and a response to
+/// the query `query_id`.
+fn deal_request(query_id: u64, candidates: Vec<u64>) -> Call {
+	Call::StorageDeals(crate::Call::create_deal_with_response {
+		query_id,
+		max_response_weight: 1_000,
+		data_root: H256::repeat_byte(1),
+		size: 100,
+		duration: 20,
+		price_per_block: 2,
+		asset: None,
+		replicas: 2,
+		rules: Default::default(),
+		verified_only: false,
+		candidates: candidates.try_into().unwrap(),
+	})
+}
+
+/// Request the deal of `deal_request` from the XCM origin `location`.
+fn request(location: MultiLocation, candidates: Vec<u64>) -> DispatchResult {
+	StorageDeals::create_deal_with_response(
+		pallet_xcm::Origin::Xcm(location).into(),
+		42,
+		1_000,
+		H256::repeat_byte(1),
+		100,
+		20,
+		2,
+		None,
+		2,
+		Default::default(),
+		false,
+		candidates.try_into().unwrap(),
+	)
+}
+
+/// The error reported for the error `error` of the pallet `P`.
+fn trap<P: PalletInfoAccess>(error: u64) -> XcmError {
+	XcmError::Trap((P::index() as u64) << 8 | error)
+}
+
+/// The program the sibling sends to dispatch `call` from the location it sends it from, paying
+/// for execution in KMD from the account of `payer`.
+fn transact(call: Call, payer: MultiLocation) -> Vec<Instruction<Call>> {
+	let fees: MultiAsset = (MultiLocation::here(), 1_000u128).into();
+	vec![
+		WithdrawAsset(fees.clone().into()),
+		BuyExecution { fees, weight_limit: Unlimited },
+		Transact {
+			origin_type: OriginKind::Xcm,
+			require_weight_at_most: 200_000,
+			call: call.encode().into(),
+		},
+		RefundSurplus,
+		DepositAsset { assets: Wild(All), max_assets: 1, beneficiary: payer },
+	]
+}
+
+#[test]
+fn deals_requested_over_xcm_are_reported_with_their_id() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		assert_ok!(request(sibling(), vec![10, 11, 12]));
+
+		assert_eq!(StorageDeals::deals(0).unwrap().owner, sovereign_account(SIBLING_ID));
+		assert_eq!(sent_xcm(), vec![response(42, Response::Assets(deal_on_sibling(0).into()))]);
+		System::assert_last_event(
+			StorageDealsEvent::DealResponseSent { query_id: 42, deal_id: Some(0) }.into(),
+		);
+	});
+}
+
+#[test]
+fn deals_are_only_requested_by_siblings_over_xcm() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+
+		assert_noop!(
+			deal_request(42, vec![10, 11, 12])
+				.dispatch(Origin::signed(1))
+				.map_err(|error| error.error),
+			BadOrigin
+		);
+		assert_noop!(
+			request(MultiLocation::parent(), vec![10, 11, 12]),
+			Error::<Test>::NotFromSibling
+		);
+		assert!(sent_xcm().is_empty());
+	});
+}
+
+#[test]
+fn failed_deal_requests_are_reported_with_the_error() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+
+		// Module errors are reported with the index of their pallet and their own.
+		assert_ok!(request(sibling(), vec![10]));
+		let not_enough_providers = trap::<StorageDeals>(5);
+		assert_eq!(
+			sent_xcm(),
+			vec![response(42, Response::ExecutionResult(Some((0, not_enough_providers))))]
+		);
+		assert_eq!(StorageDeals::next_deal_id(), 0);
+		System::assert_last_event(
+			StorageDealsEvent::DealResponseSent { query_id: 42, deal_id: None }.into(),
+		);
+
+		// The user 3 of the sibling cannot pay for the deal, which is reported to the sibling.
+		let user = sibling().pushed_with_interior(sibling_user(3)).unwrap();
+		assert_ok!(request(user, vec![10, 11, 12]));
+		let insufficient_balance = trap::<Balances>(2);
+		assert_eq!(
+			sent_xcm()[1],
+			response(42, Response::ExecutionResult(Some((0, insufficient_balance))))
+		);
+		assert_eq!(StorageDeals::next_deal_id(), 0);
+		assert_eq!(StorageDeals::used_capacity(10), 0);
+
+		// Other errors keep their meaning.
+		assert_eq!(StorageDeals::xcm_error(ArithmeticError::Overflow.into()), XcmError::Overflow);
+		assert_eq!(StorageDeals::xcm_error(BadOrigin.into()), XcmError::BadOrigin);
+	});
+}
+
+#[test]
+fn deals_are_not_created_if_the_response_cannot_be_sent() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+		let unreachable = MultiLocation::new(1, X1(Parachain(UNREACHABLE_ID)));
+
+		// Dispatched, so that the deal is rolled back with the failed call.
+		assert_noop!(
+			deal_request(42, vec![10, 11, 12])
+				.dispatch(pallet_xcm::Origin::Xcm(unreachable).into())
+				.map_err(|error| error.error),
+			Error::<Test>::ResponseFailed
+		);
+	});
+}
+
+#[test]
+fn siblings_buy_storage_from_their_sovereign_account_over_xcm() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+
+		let message = Xcm(transact(deal_request(42, vec![10, 11, 12]), sibling()));
+		let outcome = XcmExecutor::<XcmConfig>::execute_xcm(sibling(), message, 1_000_000);
+		assert_eq!(outcome.ensure_complete(), Ok(()));
+
+		let sovereign = sovereign_account(SIBLING_ID);
+		let deal = StorageDeals::deals(0).unwrap();
+		assert_eq!(deal.owner, sovereign);
+		assert_eq!(deal.providers.into_inner(), vec![10, 11]);
+		assert_eq!(Balances::reserved_balance(&sovereign), 2 * 20 * 2);
+		assert_eq!(sent_xcm(), vec![response(42, Response::Assets(deal_on_sibling(0).into()))]);
+	});
+}
+
+#[test]
+fn users_of_siblings_buy_storage_from_their_derived_account_over_xcm() {
+	new_test_ext().execute_with(|| {
+		add_providers();
+
+		let user = sibling().pushed_with_interior(sibling_user(5)).unwrap();
+		let mut instructions = vec![DescendOrigin(X1(sibling_user(5)))];
+		instructions.extend(transact(deal_request(7, vec![10, 11, 12]), user));
+
+		// The runtime lets descended programs pay for themselves, the mock runs them on credit.
+		let outcome = XcmExecutor::<XcmConfig>::execute_xcm_in_credit(
+			sibling(),
+			Xcm(instructions),
+			1_000_000,
+			1_000_000,
+		);
+		assert_eq!(outcome.ensure_complete(), Ok(()));
+
+		let derived = derived_account(SIBLING_ID, 5);
+		assert_eq!(StorageDeals::deals(0).unwrap().owner, derived);
+		assert_eq!(Balances::reserved_balance(&derived), 2 * 20 * 2);
+		assert_eq!(Balances::reserved_balance(&sovereign_account(SIBLING_ID)), 0);
+		assert_eq!(sent_xcm(), vec![response(7, Response::Assets(deal_on_sibling(0).into()))]);
+	});
+}
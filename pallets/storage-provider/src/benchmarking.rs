@@ -0,0 +1,156 @@
+//! Benchmarks for the storage provider pallet.
+
+use super::*;
+
+use crate::Pallet as StorageProvider;
+use codec::Encode;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::{Currency, EnsureOrigin, Get},
+};
+use frame_system::RawOrigin;
+use kumandra_primitive::plot;
+use sp_core::{crypto::KeyTypeId, sr25519};
+use sp_runtime::traits::{Bounded, Hash, One};
+use sp_std::vec::Vec;
+
+/// The key type of the benchmark keys generated in the keystore of the benchmarks.
+const BENCHMARK_KEY: KeyTypeId = KeyTypeId(*b"bnch");
+
+fn funded_provider<T: Config>() -> T::AccountId {
+	let who: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2u32.into());
+	who
+}
+
+fn register<T: Config>(who: &T::AccountId) -> DispatchResult {
+	StorageProvider::<T>::register_plot(
+		RawOrigin::Signed(who.clone()).into(),
+		T::MinPlotPieces::get(),
+	)
+}
+
+/// Register a new benchmark key for `who` and sign an attestation of the current block with it.
+fn signed_attestation<T: Config>(
+	who: &T::AccountId,
+) -> Result<(T::BlockNumber, sr25519::Signature), &'static str> {
+	let key = sp_io::crypto::sr25519_generate(BENCHMARK_KEY, None);
+	StorageProvider::<T>::set_benchmark_key(RawOrigin::Signed(who.clone()).into(), key)?;
+	let measured_at = frame_system::Pallet::<T>::block_number();
+	let payload = attestation_payload(who, &HardwareBenchmark::default(), &measured_at);
+	let signature = sp_io::crypto::sr25519_sign(BENCHMARK_KEY, &key, &payload)
+		.ok_or("the keystore holds the generated key")?;
+	Ok((measured_at, signature))
+}
+
+/// Open the challenge of the plot of `who` with a seed auditing the last chunk of every piece,
+/// the slowest chunk to verify.
+fn audit_last_chunk<T: Config>(who: &T::AccountId) {
+	let challenge_at =
+		StorageProvider::<T>::plots(who).expect("registered first; qed").challenge_at;
+	let randomness = (0u32..)
+		.map(|seed| T::Hashing::hash(&seed.encode()))
+		.find(|hash| plot::audit_offset(hash.as_ref(), &who.encode()) == plot::CHUNKS - 1)
+		.expect("one hash in `CHUNKS` audits the last chunk; qed");
+	frame_system::BlockHash::<T>::insert(challenge_at, randomness);
+	frame_system::Pallet::<T>::set_block_number(challenge_at + One::one());
+}
+
+benchmarks! {
+	register_plot {
+		let caller = funded_provider::<T>();
+	}: _(RawOrigin::Signed(caller.clone()), T::MinPlotPieces::get())
+	verify {
+		assert!(Plots::<T>::contains_key(&caller));
+	}
+
+	submit_proof {
+		let caller = funded_provider::<T>();
+		T::Currency::make_free_balance_be(
+			&StorageProvider::<T>::account_id(),
+			BalanceOf::<T>::max_value() / 2u32.into(),
+		);
+		register::<T>(&caller)?;
+		// The verification costs the same for plots of any size, but solutions are only quick to
+		// find in small ones: every piece of a plot of `SOLUTIONS` pieces is a solution.
+		Plots::<T>::mutate(&caller, |info| {
+			if let Some(info) = info {
+				info.pieces = plot::SOLUTIONS.into();
+			}
+		});
+		audit_last_chunk::<T>(&caller);
+		let solutions: Solutions = (0..u64::from(plot::SOLUTIONS))
+			.collect::<Vec<_>>()
+			.try_into()
+			.expect("`Solutions` holds `SOLUTIONS` indices; qed");
+	}: _(RawOrigin::Signed(caller.clone()), solutions)
+	verify {
+		assert!(StorageProvider::<T>::plots(&caller).unwrap().last_proven.is_some());
+	}
+
+	remove_plot {
+		let caller = funded_provider::<T>();
+		register::<T>(&caller)?;
+		let (measured_at, signature) = signed_attestation::<T>(&caller)?;
+		StorageProvider::<T>::attest_hardware(
+			RawOrigin::Signed(caller.clone()).into(),
+			Default::default(),
+			measured_at,
+			signature,
+		)?;
+		StorageProvider::<T>::set_provider_policy(
+			RawOrigin::Signed(caller.clone()).into(),
+			Default::default(),
+		)?;
+		StorageProvider::<T>::declare_profile(
+			RawOrigin::Signed(caller.clone()).into(),
+			Default::default(),
+			caller.clone(),
+		)?;
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!Plots::<T>::contains_key(&caller));
+	}
+
+	set_benchmark_key {
+		let caller = funded_provider::<T>();
+		register::<T>(&caller)?;
+		let key = sr25519::Public::from_raw([1; 32]);
+	}: _(RawOrigin::Signed(caller.clone()), key)
+	verify {
+		assert_eq!(BenchmarkKeys::<T>::get(&caller), Some(key));
+	}
+
+	attest_hardware {
+		let caller = funded_provider::<T>();
+		register::<T>(&caller)?;
+		let (measured_at, signature) = signed_attestation::<T>(&caller)?;
+	}: _(RawOrigin::Signed(caller.clone()), Default::default(), measured_at, signature)
+	verify {
+		assert!(Attestations::<T>::contains_key(&caller));
+	}
+
+	set_provider_policy {
+		let caller = funded_provider::<T>();
+		register::<T>(&caller)?;
+	}: _(RawOrigin::Signed(caller.clone()), Default::default())
+	verify {
+		assert!(Policies::<T>::contains_key(&caller));
+	}
+
+	set_hardware_requirements {
+		let origin = T::ManagerOrigin::successful_origin();
+	}: _<T::Origin>(origin, Default::default())
+
+	declare_profile {
+		let caller = funded_provider::<T>();
+		register::<T>(&caller)?;
+		let region: Region = sp_std::vec![b'x'; 32].try_into().expect("regions hold 32 bytes; qed");
+	}: _(RawOrigin::Signed(caller.clone()), region, caller.clone())
+	verify {
+		assert!(Profiles::<T>::contains_key(&caller));
+	}
+}
+
+impl_benchmark_test_suite!(StorageProvider, crate::mock::new_test_ext(), crate::mock::Test);
@@ -0,0 +1,639 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Storage providers and the proofs of the disk capacity they commit.
+//!
+//! A provider plots disk space with sealed pieces derived from its account (see
+//! [`kumandra_primitive::plot`]) and registers the size of the plot. The chain then challenges the
+//! plot every `ChallengePeriod` blocks. The challenge is seeded by the hash of the block it was
+//! issued at, and has to be answered within `ProofWindow` blocks with the pieces of the plot whose
+//! audited chunk scores under the target. The runtime recomputes the audited chunks of the
+//! solutions only, while a provider that did not keep its plot has to reseal the whole plot to
+//! find them. Capacity only counts as proven while the provider keeps answering its challenges,
+//! and every answered challenge is rewarded from the pallet pot by the size of the plot.
+//!
+//! Providers also publish the hardware benchmark their node measured. The node keeps a
+//! benchmark key next to the plot and signs every measurement with it, binding the results to the
+//! provider and to the block they were measured at, and the chain only accepts attestations signed
+//! by the key the provider registered. Governance sets the minimum requirements, every provider
+//! can set a stricter policy of its own, and only providers with a recent attestation meeting both
+//! accept new deals. To let clients spread their replicas, every provider declares the region it
+//! stores data in and the operator running it.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, ExistenceRequirement, ReservableCurrency},
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use kumandra_primitive::{plot, storage::StorageProviders};
+	use sp_core::sr25519;
+	use sp_runtime::{
+		traits::{AccountIdConversion, One, SaturatedConversion, Saturating, Verify, Zero},
+		Permill,
+	};
+	use sp_std::vec::Vec;
+
+	use crate::WeightInfo;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// The name of a region, e.g. `eu-west`.
+	pub type Region = BoundedVec<u8, ConstU32<32>>;
+
+	/// Where a storage provider keeps its data and who runs it.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct ProviderProfile<AccountId> {
+		/// The region the provider stores data in.
+		pub region: Region,
+		/// The operator running the provider. Operators running several providers have to
+		/// declare the same account for all of them.
+		pub operator: AccountId,
+	}
+
+	/// Hardware benchmark results of a storage provider's node.
+	///
+	/// Throughputs are in MB/s, as measured by `sc_sysinfo`, and the free space is in bytes.
+	#[derive(
+		Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+	)]
+	pub struct HardwareBenchmark {
+		/// Blake2 hashing throughput of the CPU.
+		pub cpu_hashrate: u64,
+		/// Memory copy throughput.
+		pub memory_memcpy: u64,
+		/// Sequential disk write throughput.
+		pub disk_sequential_write: u64,
+		/// Random disk write throughput.
+		pub disk_random_write: u64,
+		/// Free space available to the plot.
+		pub free_space: u64,
+	}
+
+	impl HardwareBenchmark {
+		/// Whether every measurement is at least the one of `minimum`.
+		pub fn meets(&self, minimum: &Self) -> bool {
+			self.cpu_hashrate >= minimum.cpu_hashrate &&
+				self.memory_memcpy >= minimum.memory_memcpy &&
+				self.disk_sequential_write >= minimum.disk_sequential_write &&
+				self.disk_random_write >= minimum.disk_random_write &&
+				self.free_space >= minimum.free_space
+		}
+	}
+
+	/// The minimum hardware a storage provider requires of its own node before it accepts new
+	/// deals, on top of the requirements set by governance.
+	#[derive(
+		Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+	)]
+	pub struct ProviderPolicy {
+		/// Minimum sequential disk write throughput, in MB/s.
+		pub min_disk_sequential_write: u64,
+		/// Minimum random disk write throughput, in MB/s.
+		pub min_disk_random_write: u64,
+		/// Minimum free space available to the plot, in bytes.
+		pub min_free_space: u64,
+	}
+
+	impl ProviderPolicy {
+		/// Whether `benchmark` satisfies the policy.
+		pub fn check(&self, benchmark: &HardwareBenchmark) -> bool {
+			benchmark.disk_sequential_write >= self.min_disk_sequential_write &&
+				benchmark.disk_random_write >= self.min_disk_random_write &&
+				benchmark.free_space >= self.min_free_space
+		}
+	}
+
+	/// A hardware benchmark published by a storage provider.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct HardwareAttestation<BlockNumber> {
+		/// The published benchmark results.
+		pub benchmark: HardwareBenchmark,
+		/// The block the benchmark was measured at.
+		pub attested_at: BlockNumber,
+	}
+
+	/// Domain separator of the messages signed by benchmark keys.
+	const ATTESTATION_CONTEXT: &[u8] = b"kumandra-hardware-attestation";
+
+	/// The message the benchmark key of `who` signs to attest `benchmark`, measured at the block
+	/// `measured_at`.
+	pub fn attestation_payload<AccountId: Encode, BlockNumber: Encode>(
+		who: &AccountId,
+		benchmark: &HardwareBenchmark,
+		measured_at: &BlockNumber,
+	) -> Vec<u8> {
+		(ATTESTATION_CONTEXT, who, benchmark, measured_at).encode()
+	}
+
+	/// The solutions answering a challenge: indices of pieces in ascending order.
+	pub type Solutions = BoundedVec<u64, ConstU32<{ plot::SOLUTIONS }>>;
+
+	/// A plot committed by a storage provider.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct PlotInfo<BlockNumber> {
+		/// Number of pieces in the plot.
+		pub pieces: u64,
+		/// The block whose hash seeds the current challenge.
+		pub challenge_at: BlockNumber,
+		/// The block at which the last challenge was answered, if any.
+		pub last_proven: Option<BlockNumber>,
+	}
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency used for plot deposits and rewards.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The deposit reserved while a plot is registered.
+		#[pallet::constant]
+		type PlotDeposit: Get<BalanceOf<Self>>;
+
+		/// The smallest plot, in pieces, that can be registered.
+		#[pallet::constant]
+		type MinPlotPieces: Get<u64>;
+
+		/// The largest plot, in pieces, that can be registered.
+		#[pallet::constant]
+		type MaxPlotPieces: Get<u64>;
+
+		/// Number of blocks between two challenges of the same plot.
+		#[pallet::constant]
+		type ChallengePeriod: Get<Self::BlockNumber>;
+
+		/// Number of blocks a provider has to answer a challenge. Must stay below the
+		/// `BlockHashCount` of the system pallet, otherwise the challenge seed is pruned.
+		#[pallet::constant]
+		type ProofWindow: Get<Self::BlockNumber>;
+
+		/// The reward per proven piece paid out from the pot on every answered challenge.
+		#[pallet::constant]
+		type RewardPerPiece: Get<BalanceOf<Self>>;
+
+		/// The id of the account holding the reward pot.
+		#[pallet::constant]
+		type PotId: Get<PalletId>;
+
+		/// Number of blocks a hardware attestation stays valid, counted from the block its
+		/// benchmark was measured at.
+		#[pallet::constant]
+		type AttestationValidity: Get<Self::BlockNumber>;
+
+		/// The origin allowed to set the hardware requirements.
+		type ManagerOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The plot registered by each storage provider.
+	#[pallet::storage]
+	#[pallet::getter(fn plots)]
+	pub type Plots<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, PlotInfo<T::BlockNumber>, OptionQuery>;
+
+	/// The latest hardware attestation of each storage provider.
+	#[pallet::storage]
+	#[pallet::getter(fn attestations)]
+	pub type Attestations<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, HardwareAttestation<T::BlockNumber>, OptionQuery>;
+
+	/// The key the node of each storage provider signs its hardware benchmarks with.
+	#[pallet::storage]
+	#[pallet::getter(fn benchmark_keys)]
+	pub type BenchmarkKeys<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, sr25519::Public, OptionQuery>;
+
+	/// The policy each storage provider set for its own hardware.
+	#[pallet::storage]
+	#[pallet::getter(fn policies)]
+	pub type Policies<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, ProviderPolicy, ValueQuery>;
+
+	/// The profile declared by each storage provider.
+	#[pallet::storage]
+	#[pallet::getter(fn profiles)]
+	pub type Profiles<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, ProviderProfile<T::AccountId>, OptionQuery>;
+
+	/// The minimum hardware a storage provider needs to accept new deals.
+	#[pallet::storage]
+	#[pallet::getter(fn hardware_requirements)]
+	pub type HardwareRequirements<T> = StorageValue<_, HardwareBenchmark, ValueQuery>;
+
+	/// The number of challenges issued to plots, not counting challenges withdrawn because their
+	/// plot was replaced or removed while they were still open.
+	#[pallet::storage]
+	#[pallet::getter(fn challenges_issued)]
+	pub type ChallengesIssued<T> = StorageValue<_, u64, ValueQuery>;
+
+	/// The number of challenges answered with a valid proof.
+	#[pallet::storage]
+	#[pallet::getter(fn proofs_accepted)]
+	pub type ProofsAccepted<T> = StorageValue<_, u64, ValueQuery>;
+
+	/// The number of challenges that closed without being answered.
+	#[pallet::storage]
+	#[pallet::getter(fn challenges_missed)]
+	pub type ChallengesMissed<T> = StorageValue<_, u64, ValueQuery>;
+
+	/// The number of open challenges that have to be answered by each block.
+	#[pallet::storage]
+	pub type ChallengeDeadlines<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, u32, ValueQuery>;
+
+	/// The capacity, in bytes, of all registered plots, proven or not.
+	#[pallet::storage]
+	#[pallet::getter(fn committed_capacity)]
+	pub type CommittedCapacity<T> = StorageValue<_, u64, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A plot was registered and its first challenge issued.
+		PlotRegistered { who: T::AccountId, pieces: u64 },
+		/// A challenge was answered and the capacity of the plot is proven.
+		PlotProven { who: T::AccountId, pieces: u64, next_challenge: T::BlockNumber },
+		/// A plot was removed and its deposit returned.
+		PlotRemoved { who: T::AccountId },
+		/// A provider was rewarded for an answered challenge.
+		ProviderRewarded { who: T::AccountId, amount: BalanceOf<T> },
+		/// A provider registered the key its node signs hardware benchmarks with.
+		BenchmarkKeySet { who: T::AccountId, key: sr25519::Public },
+		/// A provider published a hardware benchmark.
+		HardwareAttested { who: T::AccountId, benchmark: HardwareBenchmark },
+		/// A provider set the policy for its own hardware.
+		ProviderPolicySet { who: T::AccountId, policy: ProviderPolicy },
+		/// The hardware requirements for accepting new deals were changed.
+		HardwareRequirementsSet { requirements: HardwareBenchmark },
+		/// A provider declared its region and operator.
+		ProfileDeclared { who: T::AccountId, region: Region, operator: T::AccountId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The plot has fewer pieces than `MinPlotPieces`.
+		PlotTooSmall,
+		/// The plot has more pieces than `MaxPlotPieces`.
+		PlotTooLarge,
+		/// The account has no registered plot.
+		NoPlot,
+		/// The block seeding the challenge has not been produced yet.
+		ChallengeNotOpen,
+		/// The proof window has passed. Register the plot again to receive a new challenge.
+		ChallengeExpired,
+		/// The solutions do not answer the challenge.
+		InvalidProof,
+		/// The account has not registered a benchmark key.
+		NoBenchmarkKey,
+		/// The attestation is not signed by the benchmark key of the account.
+		InvalidAttestationSignature,
+		/// The benchmark was measured at a future block or has already expired.
+		StaleAttestation,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			// The challenges still open at the previous block close unanswered.
+			let missed = ChallengeDeadlines::<T>::take(now.saturating_sub(One::one()));
+			if missed > 0 {
+				ChallengesMissed::<T>::mutate(|total| *total = total.saturating_add(missed.into()));
+			}
+			T::DbWeight::get().reads_writes(2, 2)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register a plot of `pieces` pieces.
+		///
+		/// Registering again replaces the previous plot and issues a new challenge. The capacity
+		/// only counts once the first challenge has been answered.
+		#[pallet::weight(T::WeightInfo::register_plot())]
+		pub fn register_plot(origin: OriginFor<T>, pieces: u64) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(pieces >= T::MinPlotPieces::get(), Error::<T>::PlotTooSmall);
+			ensure!(pieces <= T::MaxPlotPieces::get(), Error::<T>::PlotTooLarge);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			match Plots::<T>::get(&who) {
+				Some(info) => {
+					Self::withdraw_challenge(&info, now);
+					Self::uncommit(info.pieces);
+				},
+				None => T::Currency::reserve(&who, T::PlotDeposit::get())?,
+			}
+
+			Plots::<T>::insert(&who, PlotInfo { pieces, challenge_at: now, last_proven: None });
+			CommittedCapacity::<T>::mutate(|total| {
+				*total = total.saturating_add(pieces.saturating_mul(plot::PIECE_SIZE as u64))
+			});
+			Self::issue_challenge(now);
+
+			Self::deposit_event(Event::PlotRegistered { who, pieces });
+			Ok(())
+		}
+
+		/// Answer the current challenge of the caller's plot with the indices of the pieces whose
+		/// audited chunk is a solution, in ascending order.
+		#[pallet::weight(T::WeightInfo::submit_proof())]
+		pub fn submit_proof(origin: OriginFor<T>, solutions: Solutions) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = frame_system::Pallet::<T>::block_number();
+
+			let (pieces, next_challenge) = Plots::<T>::try_mutate(&who, |maybe_plot| {
+				let info = maybe_plot.as_mut().ok_or(Error::<T>::NoPlot)?;
+
+				ensure!(now > info.challenge_at, Error::<T>::ChallengeNotOpen);
+				ensure!(
+					now <= info.challenge_at.saturating_add(T::ProofWindow::get()),
+					Error::<T>::ChallengeExpired
+				);
+
+				let randomness = frame_system::Pallet::<T>::block_hash(info.challenge_at);
+				ensure!(
+					plot::verify_solutions(
+						&plot::plot_seed(&who.encode()),
+						randomness.as_ref(),
+						Self::audit_offset(&who, info),
+						info.pieces,
+						&solutions,
+					),
+					Error::<T>::InvalidProof
+				);
+
+				Self::close_challenge(info);
+				info.last_proven = Some(now);
+				info.challenge_at = now.saturating_add(T::ChallengePeriod::get());
+				Ok::<_, DispatchError>((info.pieces, info.challenge_at))
+			})?;
+			ProofsAccepted::<T>::mutate(|accepted| *accepted = accepted.saturating_add(1));
+			Self::issue_challenge(next_challenge);
+
+			Self::deposit_event(Event::PlotProven { who: who.clone(), pieces, next_challenge });
+			Self::reward(&who, pieces);
+			Ok(())
+		}
+
+		/// Remove the caller's plot and return its deposit.
+		#[pallet::weight(T::WeightInfo::remove_plot())]
+		pub fn remove_plot(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let info = Plots::<T>::take(&who).ok_or(Error::<T>::NoPlot)?;
+			Self::withdraw_challenge(&info, frame_system::Pallet::<T>::block_number());
+			Self::uncommit(info.pieces);
+			BenchmarkKeys::<T>::remove(&who);
+			Attestations::<T>::remove(&who);
+			Policies::<T>::remove(&who);
+			Profiles::<T>::remove(&who);
+			T::Currency::unreserve(&who, T::PlotDeposit::get());
+
+			Self::deposit_event(Event::PlotRemoved { who });
+			Ok(())
+		}
+
+		/// Register the key the caller's node signs its hardware benchmarks with.
+		///
+		/// Drops the current attestation, which was signed by the previous key.
+		#[pallet::weight(T::WeightInfo::set_benchmark_key())]
+		pub fn set_benchmark_key(origin: OriginFor<T>, key: sr25519::Public) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Plots::<T>::contains_key(&who), Error::<T>::NoPlot);
+
+			BenchmarkKeys::<T>::insert(&who, key);
+			Attestations::<T>::remove(&who);
+
+			Self::deposit_event(Event::BenchmarkKeySet { who, key });
+			Ok(())
+		}
+
+		/// Publish the hardware benchmark the caller's node measured at the block `measured_at`.
+		///
+		/// `signature` is the signature of the benchmark key of the caller over
+		/// [`attestation_payload`]. Replaces any earlier attestation. The attestation expires
+		/// `AttestationValidity` blocks after the measurement.
+		#[pallet::weight(T::WeightInfo::attest_hardware())]
+		pub fn attest_hardware(
+			origin: OriginFor<T>,
+			benchmark: HardwareBenchmark,
+			measured_at: T::BlockNumber,
+			signature: sr25519::Signature,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Plots::<T>::contains_key(&who), Error::<T>::NoPlot);
+			let key = BenchmarkKeys::<T>::get(&who).ok_or(Error::<T>::NoBenchmarkKey)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				measured_at <= now &&
+					now <= measured_at.saturating_add(T::AttestationValidity::get()),
+				Error::<T>::StaleAttestation
+			);
+			let payload = attestation_payload(&who, &benchmark, &measured_at);
+			ensure!(signature.verify(&payload[..], &key), Error::<T>::InvalidAttestationSignature);
+
+			Attestations::<T>::insert(
+				&who,
+				HardwareAttestation { benchmark: benchmark.clone(), attested_at: measured_at },
+			);
+
+			Self::deposit_event(Event::HardwareAttested { who, benchmark });
+			Ok(())
+		}
+
+		/// Set the minimum hardware a storage provider needs to accept new deals.
+		#[pallet::weight(T::WeightInfo::set_hardware_requirements())]
+		pub fn set_hardware_requirements(
+			origin: OriginFor<T>,
+			requirements: HardwareBenchmark,
+		) -> DispatchResult {
+			T::ManagerOrigin::ensure_origin(origin)?;
+
+			HardwareRequirements::<T>::put(&requirements);
+
+			Self::deposit_event(Event::HardwareRequirementsSet { requirements });
+			Ok(())
+		}
+
+		/// Set the minimum hardware the caller requires of its own node before it accepts new
+		/// deals.
+		#[pallet::weight(T::WeightInfo::set_provider_policy())]
+		pub fn set_provider_policy(origin: OriginFor<T>, policy: ProviderPolicy) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Plots::<T>::contains_key(&who), Error::<T>::NoPlot);
+
+			Policies::<T>::insert(&who, &policy);
+
+			Self::deposit_event(Event::ProviderPolicySet { who, policy });
+			Ok(())
+		}
+
+		/// Declare the region the caller stores data in and the operator running it.
+		#[pallet::weight(T::WeightInfo::declare_profile())]
+		pub fn declare_profile(
+			origin: OriginFor<T>,
+			region: Region,
+			operator: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Plots::<T>::contains_key(&who), Error::<T>::NoPlot);
+
+			Profiles::<T>::insert(
+				&who,
+				ProviderProfile { region: region.clone(), operator: operator.clone() },
+			);
+
+			Self::deposit_event(Event::ProfileDeclared { who, region, operator });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The account holding the reward pot.
+		pub fn account_id() -> T::AccountId {
+			T::PotId::get().into_account_truncating()
+		}
+
+		/// The offset of the chunks audited by the current challenge of a plot.
+		pub fn audit_offset(who: &T::AccountId, info: &PlotInfo<T::BlockNumber>) -> u32 {
+			let randomness = frame_system::Pallet::<T>::block_hash(info.challenge_at);
+			plot::audit_offset(randomness.as_ref(), &who.encode())
+		}
+
+		/// Whether the capacity of a plot counts as proven at `now`.
+		pub fn is_proven(info: &PlotInfo<T::BlockNumber>, now: T::BlockNumber) -> bool {
+			info.last_proven.is_some() && Self::is_open(info, now)
+		}
+
+		/// Whether the current challenge of a plot can still be answered at `now`.
+		pub fn is_open(info: &PlotInfo<T::BlockNumber>, now: T::BlockNumber) -> bool {
+			now <= info.challenge_at.saturating_add(T::ProofWindow::get())
+		}
+
+		/// The share of the closed challenges that were answered, or `None` if no challenge has
+		/// closed yet. Challenges that can still be answered are left out.
+		pub fn proof_success_rate() -> Option<Permill> {
+			let accepted = ProofsAccepted::<T>::get();
+			let closed = accepted.saturating_add(ChallengesMissed::<T>::get());
+			if closed == 0 {
+				return None
+			}
+			Some(Permill::from_rational(accepted, closed))
+		}
+
+		/// The capacity, in bytes, `who` currently has proven.
+		pub fn proven_capacity(who: &T::AccountId) -> u64 {
+			let now = frame_system::Pallet::<T>::block_number();
+			Plots::<T>::get(who)
+				.filter(|info| Self::is_proven(info, now))
+				.map(|info| info.pieces.saturating_mul(plot::PIECE_SIZE as u64))
+				.unwrap_or_default()
+		}
+
+		/// Whether `who` has a valid hardware attestation meeting the current requirements and
+		/// its own policy.
+		pub fn meets_hardware_requirements(who: &T::AccountId) -> bool {
+			let now = frame_system::Pallet::<T>::block_number();
+			Attestations::<T>::get(who).map_or(false, |attestation| {
+				now <= attestation.attested_at.saturating_add(T::AttestationValidity::get()) &&
+					attestation.benchmark.meets(&HardwareRequirements::<T>::get()) &&
+					Policies::<T>::get(who).check(&attestation.benchmark)
+			})
+		}
+
+		/// Whether `who` accepts new deals: its capacity is proven, its hardware attestation
+		/// meets the requirements and its policy, and it declared its profile.
+		pub fn accepts_deals(who: &T::AccountId) -> bool {
+			Self::proven_capacity(who) > 0 &&
+				Self::meets_hardware_requirements(who) &&
+				Profiles::<T>::contains_key(who)
+		}
+
+		/// Count a challenge seeded by the block `challenge_at` until it is answered or closes.
+		fn issue_challenge(challenge_at: T::BlockNumber) {
+			ChallengesIssued::<T>::mutate(|issued| *issued = issued.saturating_add(1));
+			ChallengeDeadlines::<T>::mutate(
+				challenge_at.saturating_add(T::ProofWindow::get()),
+				|open| *open = open.saturating_add(1),
+			);
+		}
+
+		/// Stop counting the current challenge of a plot as open.
+		fn close_challenge(info: &PlotInfo<T::BlockNumber>) {
+			ChallengeDeadlines::<T>::mutate_exists(
+				info.challenge_at.saturating_add(T::ProofWindow::get()),
+				|open| *open = open.map(|open| open.saturating_sub(1)).filter(|open| *open > 0),
+			);
+		}
+
+		/// Stop counting the challenge of a plot that is replaced or removed while it can still
+		/// be answered.
+		fn withdraw_challenge(info: &PlotInfo<T::BlockNumber>, now: T::BlockNumber) {
+			if Self::is_open(info, now) {
+				ChallengesIssued::<T>::mutate(|issued| *issued = issued.saturating_sub(1));
+				Self::close_challenge(info);
+			}
+		}
+
+		/// Stop counting the capacity of a plot of `pieces` pieces as committed.
+		fn uncommit(pieces: u64) {
+			CommittedCapacity::<T>::mutate(|total| {
+				*total = total.saturating_sub(pieces.saturating_mul(plot::PIECE_SIZE as u64))
+			});
+		}
+
+		/// Pay the reward for `pieces` proven pieces out of the pot, as far as it is funded.
+		fn reward(who: &T::AccountId, pieces: u64) {
+			let pot = Self::account_id();
+			let available =
+				T::Currency::free_balance(&pot).saturating_sub(T::Currency::minimum_balance());
+			let amount =
+				T::RewardPerPiece::get().saturating_mul(pieces.saturated_into()).min(available);
+
+			if !amount.is_zero() &&
+				T::Currency::transfer(&pot, who, amount, ExistenceRequirement::KeepAlive).is_ok()
+			{
+				Self::deposit_event(Event::ProviderRewarded { who: who.clone(), amount });
+			}
+		}
+	}
+
+	impl<T: Config> StorageProviders<T::AccountId> for Pallet<T> {
+		fn accepts_deals(who: &T::AccountId) -> bool {
+			Self::accepts_deals(who)
+		}
+
+		fn proven_capacity(who: &T::AccountId) -> u64 {
+			Self::proven_capacity(who)
+		}
+
+		fn profile(who: &T::AccountId) -> Option<(Vec<u8>, T::AccountId)> {
+			Profiles::<T>::get(who).map(|profile| (profile.region.into_inner(), profile.operator))
+		}
+	}
+}
@@ -0,0 +1,116 @@
+use crate as pallet_storage_provider;
+use frame_support::{parameter_types, traits::Everything, PalletId};
+use frame_system::{self as system, EnsureRoot};
+use sp_core::H256;
+use sp_keystore::{testing::KeyStore, KeystoreExt};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::sync::Arc;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		StorageProvider: pallet_storage_provider::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const PlotDeposit: u64 = 10;
+	pub const MinPlotPieces: u64 = 2;
+	pub const MaxPlotPieces: u64 = 1 << 10;
+	pub const ChallengePeriod: u64 = 10;
+	pub const ProofWindow: u64 = 5;
+	pub const RewardPerPiece: u64 = 2;
+	pub const PotId: PalletId = PalletId(*b"PotStore");
+	pub const AttestationValidity: u64 = 100;
+}
+
+impl pallet_storage_provider::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type PlotDeposit = PlotDeposit;
+	type MinPlotPieces = MinPlotPieces;
+	type MaxPlotPieces = MaxPlotPieces;
+	type ChallengePeriod = ChallengePeriod;
+	type ProofWindow = ProofWindow;
+	type RewardPerPiece = RewardPerPiece;
+	type PotId = PotId;
+	type AttestationValidity = AttestationValidity;
+	type ManagerOrigin = EnsureRoot<u64>;
+	type WeightInfo = ();
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (StorageProvider::account_id(), 1_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	// The benchmarks sign hardware attestations with keys generated in the keystore.
+	ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
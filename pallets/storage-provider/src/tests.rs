@@ -0,0 +1,502 @@
+use crate::{
+	attestation_payload, mock::*, Error, Event as StorageProviderEvent, HardwareBenchmark,
+	ProviderPolicy, ProviderProfile, Region, Solutions,
+};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use kumandra_primitive::{plot, storage::StorageProviders};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{DispatchError, Permill};
+
+fn register(who: u64, pieces: u64) {
+	assert_ok!(StorageProvider::register_plot(Origin::signed(who), pieces));
+}
+
+/// The solutions to the current challenge of `who`, searched in the plot of `plotter`.
+fn solutions(who: u64, plotter: u64) -> Solutions {
+	let info = StorageProvider::plots(who).unwrap();
+	let randomness = System::block_hash(info.challenge_at);
+	let offset = StorageProvider::audit_offset(&who, &info);
+	let seed = plot::plot_seed(&plotter.encode());
+	let chunks = (0..info.pieces).map(|index| plot::encode_chunk(&seed, index, offset));
+	plot::find_solutions(randomness.as_bytes(), info.pieces, chunks)
+		.try_into()
+		.unwrap()
+}
+
+fn proof(who: u64) -> Solutions {
+	solutions(who, who)
+}
+
+fn declare(who: u64, region: &[u8], operator: u64) {
+	let region = region.to_vec().try_into().unwrap();
+	assert_ok!(StorageProvider::declare_profile(Origin::signed(who), region, operator));
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		let now = System::block_number();
+		frame_system::BlockHash::<Test>::insert(now, H256::repeat_byte(now as u8));
+		System::set_block_number(now + 1);
+		StorageProvider::on_initialize(now + 1);
+	}
+}
+
+#[test]
+fn register_plot_reserves_deposit_once() {
+	new_test_ext().execute_with(|| {
+		register(1, 8);
+		assert_eq!(Balances::reserved_balance(&1), 10);
+		System::assert_last_event(
+			StorageProviderEvent::PlotRegistered { who: 1, pieces: 8 }.into(),
+		);
+
+		register(1, 16);
+		assert_eq!(Balances::reserved_balance(&1), 10);
+		assert_eq!(StorageProvider::plots(1).unwrap().pieces, 16);
+	});
+}
+
+#[test]
+fn plot_size_is_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			StorageProvider::register_plot(Origin::signed(1), 1),
+			Error::<Test>::PlotTooSmall
+		);
+		assert_noop!(
+			StorageProvider::register_plot(Origin::signed(1), (1 << 10) + 1),
+			Error::<Test>::PlotTooLarge
+		);
+	});
+}
+
+#[test]
+fn proof_is_accepted_within_the_window_only() {
+	new_test_ext().execute_with(|| {
+		register(1, 8);
+		assert_noop!(
+			StorageProvider::submit_proof(Origin::signed(1), Default::default()),
+			Error::<Test>::ChallengeNotOpen
+		);
+
+		run_to_block(2);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+		System::assert_has_event(
+			StorageProviderEvent::PlotProven { who: 1, pieces: 8, next_challenge: 12 }.into(),
+		);
+
+		run_to_block(18);
+		let solutions = proof(1);
+		assert_noop!(
+			StorageProvider::submit_proof(Origin::signed(1), solutions),
+			Error::<Test>::ChallengeExpired
+		);
+	});
+}
+
+#[test]
+fn invalid_proofs_are_rejected() {
+	new_test_ext().execute_with(|| {
+		register(1, 8);
+		register(2, 8);
+		run_to_block(2);
+
+		assert_noop!(
+			StorageProvider::submit_proof(Origin::signed(3), proof(1)),
+			Error::<Test>::NoPlot
+		);
+		assert_noop!(
+			StorageProvider::submit_proof(Origin::signed(1), Default::default()),
+			Error::<Test>::InvalidProof
+		);
+
+		// Every piece of a small plot is a solution, only some pieces of a large one are.
+		register(1, 1 << 10);
+		run_to_block(3);
+		let mut all_pieces = proof(1).into_inner();
+		all_pieces
+			.iter_mut()
+			.enumerate()
+			.for_each(|(index, piece)| *piece = index as u64);
+		assert_noop!(
+			StorageProvider::submit_proof(Origin::signed(1), all_pieces.try_into().unwrap()),
+			Error::<Test>::InvalidProof
+		);
+
+		// A plot derived from another account does not prove anything.
+		assert_noop!(
+			StorageProvider::submit_proof(Origin::signed(1), solutions(1, 2)),
+			Error::<Test>::InvalidProof
+		);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+	});
+}
+
+#[test]
+fn proven_capacity_lapses_without_proofs() {
+	new_test_ext().execute_with(|| {
+		register(1, 8);
+		assert_eq!(StorageProvider::proven_capacity(&1), 0);
+
+		run_to_block(2);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+		assert_eq!(StorageProvider::proven_capacity(&1), 8 * plot::PIECE_SIZE as u64);
+
+		// The next challenge opens at block 12 and has to be answered by block 17.
+		run_to_block(13);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+		assert_eq!(StorageProvider::plots(1).unwrap().challenge_at, 23);
+
+		run_to_block(28);
+		assert_eq!(StorageProvider::proven_capacity(&1), 8 * plot::PIECE_SIZE as u64);
+		run_to_block(29);
+		assert_eq!(StorageProvider::proven_capacity(&1), 0);
+	});
+}
+
+#[test]
+fn answered_challenges_are_rewarded_from_the_pot() {
+	new_test_ext().execute_with(|| {
+		register(1, 8);
+		run_to_block(2);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+
+		assert_eq!(Balances::free_balance(&1), 100 - 10 + 16);
+		assert_eq!(Balances::free_balance(&StorageProvider::account_id()), 1_000 - 16);
+		System::assert_last_event(
+			StorageProviderEvent::ProviderRewarded { who: 1, amount: 16 }.into(),
+		);
+	});
+}
+
+#[test]
+fn rewards_grow_with_the_proven_plot_size() {
+	new_test_ext().execute_with(|| {
+		register(1, 2);
+		register(2, 16);
+		run_to_block(2);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(2), proof(2)));
+
+		assert_eq!(Balances::free_balance(&1), 100 - 10 + 2 * 2);
+		assert_eq!(Balances::free_balance(&2), 100 - 10 + 16 * 2);
+	});
+}
+
+#[test]
+fn remove_plot_returns_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(StorageProvider::remove_plot(Origin::signed(1)), Error::<Test>::NoPlot);
+
+		register(1, 8);
+		assert_ok!(StorageProvider::remove_plot(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert!(StorageProvider::plots(1).is_none());
+	});
+}
+
+fn benchmark(disk_sequential_write: u64) -> HardwareBenchmark {
+	HardwareBenchmark {
+		cpu_hashrate: 1_000,
+		memory_memcpy: 10_000,
+		disk_sequential_write,
+		disk_random_write: 500,
+		free_space: 1 << 40,
+	}
+}
+
+/// The benchmark key of the node of `who`.
+fn benchmark_key(who: u64) -> sr25519::Pair {
+	sr25519::Pair::from_string(&format!("//Bench{}", who), None).unwrap()
+}
+
+/// The signature of the benchmark key of `who` attesting `benchmark`, measured at `measured_at`.
+fn sign(who: u64, benchmark: &HardwareBenchmark, measured_at: u64) -> sr25519::Signature {
+	benchmark_key(who).sign(&attestation_payload(&who, benchmark, &measured_at))
+}
+
+/// Register the benchmark key of `who` and publish `benchmark`, measured at the current block.
+fn attest(who: u64, benchmark: HardwareBenchmark) {
+	assert_ok!(StorageProvider::set_benchmark_key(
+		Origin::signed(who),
+		benchmark_key(who).public()
+	));
+	let now = System::block_number();
+	let signature = sign(who, &benchmark, now);
+	assert_ok!(StorageProvider::attest_hardware(Origin::signed(who), benchmark, now, signature));
+}
+
+#[test]
+fn hardware_requirements_are_set_by_manager() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			StorageProvider::set_hardware_requirements(Origin::signed(1), benchmark(950)),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(StorageProvider::set_hardware_requirements(Origin::root(), benchmark(950)));
+		assert_eq!(StorageProvider::hardware_requirements(), benchmark(950));
+		System::assert_last_event(
+			StorageProviderEvent::HardwareRequirementsSet { requirements: benchmark(950) }.into(),
+		);
+	});
+}
+
+#[test]
+fn attesting_hardware_requires_a_plot_and_a_benchmark_key() {
+	new_test_ext().execute_with(|| {
+		let key = benchmark_key(1).public();
+		assert_noop!(
+			StorageProvider::set_benchmark_key(Origin::signed(1), key),
+			Error::<Test>::NoPlot
+		);
+		assert_noop!(
+			StorageProvider::attest_hardware(
+				Origin::signed(1),
+				benchmark(1_000),
+				1,
+				sign(1, &benchmark(1_000), 1)
+			),
+			Error::<Test>::NoPlot
+		);
+
+		register(1, 8);
+		assert_noop!(
+			StorageProvider::attest_hardware(
+				Origin::signed(1),
+				benchmark(1_000),
+				1,
+				sign(1, &benchmark(1_000), 1)
+			),
+			Error::<Test>::NoBenchmarkKey
+		);
+
+		assert_ok!(StorageProvider::set_benchmark_key(Origin::signed(1), key));
+		assert_eq!(StorageProvider::benchmark_keys(1), Some(key));
+		System::assert_last_event(StorageProviderEvent::BenchmarkKeySet { who: 1, key }.into());
+
+		assert_ok!(StorageProvider::attest_hardware(
+			Origin::signed(1),
+			benchmark(1_000),
+			1,
+			sign(1, &benchmark(1_000), 1)
+		));
+		assert_eq!(StorageProvider::attestations(1).unwrap().benchmark, benchmark(1_000));
+		System::assert_last_event(
+			StorageProviderEvent::HardwareAttested { who: 1, benchmark: benchmark(1_000) }.into(),
+		);
+
+		assert_ok!(StorageProvider::remove_plot(Origin::signed(1)));
+		assert!(StorageProvider::benchmark_keys(1).is_none());
+		assert!(StorageProvider::attestations(1).is_none());
+	});
+}
+
+#[test]
+fn attestations_are_bound_to_the_signed_measurement() {
+	new_test_ext().execute_with(|| {
+		register(1, 8);
+		register(2, 8);
+		assert_ok!(StorageProvider::set_benchmark_key(
+			Origin::signed(1),
+			benchmark_key(1).public()
+		));
+		run_to_block(5);
+
+		let attest = |benchmark: HardwareBenchmark, measured_at, signature| {
+			StorageProvider::attest_hardware(Origin::signed(1), benchmark, measured_at, signature)
+		};
+		// Signed by another key.
+		assert_noop!(
+			attest(benchmark(1_000), 5, sign(2, &benchmark(1_000), 5)),
+			Error::<Test>::InvalidAttestationSignature
+		);
+		// Signed for another provider.
+		assert_noop!(
+			attest(
+				benchmark(1_000),
+				5,
+				benchmark_key(1).sign(&attestation_payload(&2u64, &benchmark(1_000), &5u64))
+			),
+			Error::<Test>::InvalidAttestationSignature
+		);
+		// Signed for other results or another block.
+		assert_noop!(
+			attest(benchmark(2_000), 5, sign(1, &benchmark(1_000), 5)),
+			Error::<Test>::InvalidAttestationSignature
+		);
+		assert_noop!(
+			attest(benchmark(1_000), 4, sign(1, &benchmark(1_000), 5)),
+			Error::<Test>::InvalidAttestationSignature
+		);
+		// Measured at a future block.
+		assert_noop!(
+			attest(benchmark(1_000), 6, sign(1, &benchmark(1_000), 6)),
+			Error::<Test>::StaleAttestation
+		);
+
+		// An earlier measurement counts from the block it was taken at.
+		assert_ok!(attest(benchmark(1_000), 3, sign(1, &benchmark(1_000), 3)));
+		assert_eq!(StorageProvider::attestations(1).unwrap().attested_at, 3);
+
+		// Replacing the key drops the attestation it signed.
+		assert_ok!(StorageProvider::set_benchmark_key(
+			Origin::signed(1),
+			benchmark_key(2).public()
+		));
+		assert!(StorageProvider::attestations(1).is_none());
+	});
+}
+
+#[test]
+fn only_proven_providers_with_sufficient_hardware_accept_deals() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StorageProvider::set_hardware_requirements(Origin::root(), benchmark(950)));
+		register(1, 8);
+		register(2, 8);
+		attest(1, benchmark(1_000));
+		attest(2, benchmark(900));
+		assert!(!StorageProvider::accepts_deals(&1));
+
+		run_to_block(2);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(2), proof(2)));
+		assert!(!StorageProvider::accepts_deals(&1));
+
+		declare(1, b"eu-west", 1);
+		declare(2, b"us-east", 2);
+		assert!(StorageProvider::accepts_deals(&1));
+		assert!(!StorageProvider::accepts_deals(&2));
+
+		// Lowering the requirements lets the slower provider in as well.
+		assert_ok!(StorageProvider::set_hardware_requirements(Origin::root(), benchmark(900)));
+		assert!(StorageProvider::accepts_deals(&2));
+	});
+}
+
+#[test]
+fn providers_only_accept_deals_within_their_own_policy() {
+	new_test_ext().execute_with(|| {
+		let policy = ProviderPolicy {
+			min_disk_sequential_write: 1_200,
+			min_disk_random_write: 0,
+			min_free_space: 0,
+		};
+		assert_noop!(
+			StorageProvider::set_provider_policy(Origin::signed(1), policy.clone()),
+			Error::<Test>::NoPlot
+		);
+
+		register(1, 8);
+		attest(1, benchmark(1_000));
+		run_to_block(2);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+		declare(1, b"eu-west", 1);
+		assert!(StorageProvider::accepts_deals(&1));
+
+		assert_ok!(StorageProvider::set_provider_policy(Origin::signed(1), policy.clone()));
+		assert_eq!(StorageProvider::policies(1), policy.clone());
+		System::assert_last_event(
+			StorageProviderEvent::ProviderPolicySet { who: 1, policy }.into(),
+		);
+		assert!(!StorageProvider::accepts_deals(&1));
+		assert!(!<StorageProvider as StorageProviders<u64>>::accepts_deals(&1));
+
+		// The node measured enough throughput, but not enough free space.
+		let policy = ProviderPolicy {
+			min_disk_sequential_write: 1_000,
+			min_disk_random_write: 500,
+			min_free_space: 1 << 41,
+		};
+		assert_ok!(StorageProvider::set_provider_policy(Origin::signed(1), policy));
+		assert!(!StorageProvider::accepts_deals(&1));
+
+		let policy = ProviderPolicy { min_free_space: 1 << 40, ..Default::default() };
+		assert_ok!(StorageProvider::set_provider_policy(Origin::signed(1), policy));
+		assert!(StorageProvider::accepts_deals(&1));
+
+		assert_ok!(StorageProvider::remove_plot(Origin::signed(1)));
+		assert_eq!(StorageProvider::policies(1), ProviderPolicy::default());
+	});
+}
+
+#[test]
+fn hardware_attestations_expire() {
+	new_test_ext().execute_with(|| {
+		register(1, 8);
+		attest(1, benchmark(1_000));
+		assert!(StorageProvider::meets_hardware_requirements(&1));
+
+		run_to_block(101);
+		assert!(StorageProvider::meets_hardware_requirements(&1));
+		run_to_block(102);
+		assert!(!StorageProvider::meets_hardware_requirements(&1));
+
+		// An expired measurement cannot be published again.
+		assert_noop!(
+			StorageProvider::attest_hardware(
+				Origin::signed(1),
+				benchmark(1_000),
+				1,
+				sign(1, &benchmark(1_000), 1)
+			),
+			Error::<Test>::StaleAttestation
+		);
+	});
+}
+
+#[test]
+fn profiles_are_declared_by_providers() {
+	new_test_ext().execute_with(|| {
+		let region: Region = b"eu-west".to_vec().try_into().unwrap();
+		assert_noop!(
+			StorageProvider::declare_profile(Origin::signed(1), region.clone(), 7),
+			Error::<Test>::NoPlot
+		);
+
+		register(1, 8);
+		declare(1, b"eu-west", 7);
+		assert_eq!(StorageProvider::profiles(1), Some(ProviderProfile { region, operator: 7 }));
+		assert_eq!(
+			<StorageProvider as StorageProviders<u64>>::profile(&1),
+			Some((b"eu-west".to_vec(), 7))
+		);
+
+		assert_ok!(StorageProvider::remove_plot(Origin::signed(1)));
+		assert!(StorageProvider::profiles(1).is_none());
+	});
+}
+
+#[test]
+fn proof_success_rate_counts_closed_challenges() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(StorageProvider::proof_success_rate(), None);
+
+		register(1, 8);
+		register(2, 16);
+		assert_eq!(StorageProvider::committed_capacity(), 24 * plot::PIECE_SIZE as u64);
+		assert_eq!(StorageProvider::challenges_issued(), 2);
+		assert_eq!(StorageProvider::proof_success_rate(), None);
+
+		// The first challenge of 1 is answered, the one of 2 is still open.
+		run_to_block(2);
+		assert_ok!(StorageProvider::submit_proof(Origin::signed(1), proof(1)));
+		assert_eq!(StorageProvider::challenges_issued(), 3);
+		assert_eq!(StorageProvider::proofs_accepted(), 1);
+		assert_eq!(StorageProvider::proof_success_rate(), Some(Permill::one()));
+
+		// The challenge of 2 expires unanswered.
+		run_to_block(7);
+		assert_eq!(StorageProvider::proof_success_rate(), Some(Permill::from_percent(50)));
+
+		// Replacing a plot withdraws its open challenge, removing one with an expired
+		// challenge does not.
+		register(1, 8);
+		assert_eq!(StorageProvider::challenges_issued(), 3);
+		assert_ok!(StorageProvider::remove_plot(Origin::signed(2)));
+		assert_eq!(StorageProvider::challenges_issued(), 3);
+		assert_eq!(StorageProvider::proof_success_rate(), Some(Permill::from_percent(50)));
+		assert_eq!(StorageProvider::committed_capacity(), 8 * plot::PIECE_SIZE as u64);
+	});
+}
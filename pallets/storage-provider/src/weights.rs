@@ -0,0 +1,149 @@
+//! Weights for `pallet_storage_provider`.
+//!
+//! `submit_proof` is benchmarked in its worst case: `SOLUTIONS` solutions audited at the last
+//! chunk of their pieces, about 16k hashes. Until the benchmarks are run on reference hardware,
+//! its weight is three times the native time of the verification, `attest_hardware` is weighed
+//! like the upstream calls verifying one sr25519 signature, and the other calls are weighed like
+//! the upstream calls with the same storage accesses. Generate the weights from
+//! `benchmarking.rs` with:
+//!
+//! ./target/release/kumandra-node benchmark pallet --chain=dev --steps=50 --repeat=20
+//! --pallet=pallet_storage_provider --extrinsic='*' --execution=wasm --wasm-execution=compiled
+//! --heap-pages=4096 --output=./pallets/storage-provider/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_storage_provider.
+pub trait WeightInfo {
+	fn register_plot() -> Weight;
+	fn submit_proof() -> Weight;
+	fn remove_plot() -> Weight;
+	fn set_benchmark_key() -> Weight;
+	fn attest_hardware() -> Weight;
+	fn set_provider_policy() -> Weight;
+	fn set_hardware_requirements() -> Weight;
+	fn declare_profile() -> Weight;
+}
+
+/// Weights for pallet_storage_provider using the Kumandra node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: StorageProvider Plots (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: StorageProvider CommittedCapacity (r:1 w:1)
+	// Storage: StorageProvider ChallengesIssued (r:1 w:1)
+	// Storage: StorageProvider ChallengeDeadlines (r:1 w:1)
+	fn register_plot() -> Weight {
+		(31_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: StorageProvider Plots (r:1 w:1)
+	// Storage: System BlockHash (r:1 w:0)
+	// Storage: StorageProvider ChallengeDeadlines (r:2 w:2)
+	// Storage: StorageProvider ProofsAccepted (r:1 w:1)
+	// Storage: StorageProvider ChallengesIssued (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn submit_proof() -> Weight {
+		(7_500_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: StorageProvider Plots (r:1 w:1)
+	// Storage: StorageProvider ChallengesIssued (r:1 w:1)
+	// Storage: StorageProvider ChallengeDeadlines (r:1 w:1)
+	// Storage: StorageProvider CommittedCapacity (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: StorageProvider BenchmarkKeys (r:0 w:1)
+	// Storage: StorageProvider Attestations (r:0 w:1)
+	// Storage: StorageProvider Policies (r:0 w:1)
+	// Storage: StorageProvider Profiles (r:0 w:1)
+	fn remove_plot() -> Weight {
+		(36_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
+	}
+	// Storage: StorageProvider Plots (r:1 w:0)
+	// Storage: StorageProvider BenchmarkKeys (r:0 w:1)
+	// Storage: StorageProvider Attestations (r:0 w:1)
+	fn set_benchmark_key() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: StorageProvider Plots (r:1 w:0)
+	// Storage: StorageProvider BenchmarkKeys (r:1 w:0)
+	// Storage: StorageProvider Attestations (r:0 w:1)
+	fn attest_hardware() -> Weight {
+		(70_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: StorageProvider Plots (r:1 w:0)
+	// Storage: StorageProvider Policies (r:0 w:1)
+	fn set_provider_policy() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: StorageProvider HardwareRequirements (r:0 w:1)
+	fn set_hardware_requirements() -> Weight {
+		(11_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: StorageProvider Plots (r:1 w:0)
+	// Storage: StorageProvider Profiles (r:0 w:1)
+	fn declare_profile() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn register_plot() -> Weight {
+		(31_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn submit_proof() -> Weight {
+		(7_500_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn remove_plot() -> Weight {
+		(36_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+	}
+	fn set_benchmark_key() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn attest_hardware() -> Weight {
+		(70_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_provider_policy() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_hardware_requirements() -> Weight {
+		(11_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn declare_profile() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
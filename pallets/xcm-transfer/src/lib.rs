@@ -0,0 +1,315 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Cross-chain transfers of KMD and foreign assets.
+//!
+//! Users send assets to accounts on other chains with reserve transfers. The pallet builds the
+//! XCM program of a transfer and executes it locally on behalf of the sender. Assets this chain
+//! is the reserve of, such as KMD and locally issued assets, are moved into the sovereign account
+//! of the destination, which is told to mint them. Foreign assets can only be sent back to the
+//! chain they are native to, which releases them from the sovereign account of this chain. The
+//! relay-chain token cannot be sent back, as the runtime denies reserve transfers to the relay
+//! chain.
+//!
+//! The sender picks the asset paying for execution on the destination and the weight limit of
+//! that execution. Assets can only be sent to the chains governance allowed, which also limits
+//! the reserve transfers of the XCM pallet.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{pallet_prelude::*, traits::Contains, weights::Weight};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::Convert;
+	use sp_std::{boxed::Box, vec, vec::Vec};
+	use xcm::{latest::prelude::*, VersionedMultiAsset, VersionedMultiLocation};
+	use xcm_executor::traits::{InvertLocation, WeightBounds};
+
+	type CallOf<T> = <T as frame_system::Config>::Call;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Converts an account into the location its assets are withdrawn from.
+		type AccountIdToMultiLocation: Convert<Self::AccountId, MultiLocation>;
+
+		/// Executes the programs of transfers.
+		type XcmExecutor: ExecuteXcm<CallOf<Self>>;
+
+		/// Weighs the programs of transfers.
+		type Weigher: WeightBounds<CallOf<Self>>;
+
+		/// The location of this chain, used to express assets as seen from the destination.
+		type LocationInverter: InvertLocation;
+
+		/// The origin allowed to change the destinations assets can be sent to.
+		type DestinationOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	/// The chains assets can be sent to.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	pub type AllowedDestinations<T: Config> =
+		StorageMap<_, Blake2_128Concat, MultiLocation, (), OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// `assets` were sent by `sender` to `dest`, paying for execution there with `fee`.
+		Transferred {
+			sender: T::AccountId,
+			assets: MultiAssets,
+			fee: MultiAsset,
+			dest: MultiLocation,
+		},
+		/// Assets can be sent to the chain `dest`.
+		DestinationAllowed { dest: MultiLocation },
+		/// Assets can no longer be sent to the chain `dest`.
+		DestinationDisallowed { dest: MultiLocation },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The asset or location uses an XCM version that cannot be converted.
+		BadVersion,
+		/// The destination is not an account on another chain.
+		InvalidDest,
+		/// Assets cannot be sent to the chain of the destination.
+		DestinationNotAllowed,
+		/// The asset is not a non-zero amount of a fungible asset identified by its location.
+		InvalidAsset,
+		/// The asset paying for execution does not have the same reserve as the sent asset.
+		DistinctReserveForAssetAndFee,
+		/// The fee is paid out of the sent asset, and is more than it.
+		FeeTooHigh,
+		/// The asset is neither native to this chain nor to the chain of the destination, or it is
+		/// native to the relay chain.
+		UnsupportedReserve,
+		/// The program of the transfer could not be weighed.
+		UnweighableMessage,
+		/// The program of the transfer failed to execute.
+		XcmExecutionFailed,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Send `asset` to the account `dest` on another chain, paying for execution there with
+		/// part of the asset.
+		///
+		/// At most `dest_weight_limit` of execution is bought on the destination.
+		#[pallet::weight(Pallet::<T>::weight_of_transfer(asset, asset, dest, dest_weight_limit))]
+		pub fn transfer(
+			origin: OriginFor<T>,
+			asset: Box<VersionedMultiAsset>,
+			dest: Box<VersionedMultiLocation>,
+			dest_weight_limit: WeightLimit,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let asset = MultiAsset::try_from(*asset).map_err(|()| Error::<T>::BadVersion)?;
+			let dest = MultiLocation::try_from(*dest).map_err(|()| Error::<T>::BadVersion)?;
+
+			Self::do_transfer(sender, asset.clone(), asset, dest, dest_weight_limit)
+		}
+
+		/// Send `asset` to the account `dest` on another chain, paying for execution there with
+		/// `fee`.
+		///
+		/// `fee` has to have the same reserve as `asset`. A fee in another asset is sent along, and
+		/// whatever is left of it after paying for execution is deposited into `dest` too. A fee in
+		/// the same asset is taken out of `asset`. At most `dest_weight_limit` of execution is
+		/// bought on the destination.
+		#[pallet::weight(Pallet::<T>::weight_of_transfer(asset, fee, dest, dest_weight_limit))]
+		pub fn transfer_with_fee(
+			origin: OriginFor<T>,
+			asset: Box<VersionedMultiAsset>,
+			fee: Box<VersionedMultiAsset>,
+			dest: Box<VersionedMultiLocation>,
+			dest_weight_limit: WeightLimit,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let asset = MultiAsset::try_from(*asset).map_err(|()| Error::<T>::BadVersion)?;
+			let fee = MultiAsset::try_from(*fee).map_err(|()| Error::<T>::BadVersion)?;
+			let dest = MultiLocation::try_from(*dest).map_err(|()| Error::<T>::BadVersion)?;
+
+			Self::do_transfer(sender, asset, fee, dest, dest_weight_limit)
+		}
+
+		/// Allow assets to be sent to the chain `dest`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn allow_destination(
+			origin: OriginFor<T>,
+			dest: Box<VersionedMultiLocation>,
+		) -> DispatchResult {
+			T::DestinationOrigin::ensure_origin(origin)?;
+			let dest = MultiLocation::try_from(*dest).map_err(|()| Error::<T>::BadVersion)?;
+
+			AllowedDestinations::<T>::insert(&dest, ());
+			Self::deposit_event(Event::DestinationAllowed { dest });
+			Ok(())
+		}
+
+		/// Stop assets from being sent to the chain `dest`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn disallow_destination(
+			origin: OriginFor<T>,
+			dest: Box<VersionedMultiLocation>,
+		) -> DispatchResult {
+			T::DestinationOrigin::ensure_origin(origin)?;
+			let dest = MultiLocation::try_from(*dest).map_err(|()| Error::<T>::BadVersion)?;
+
+			AllowedDestinations::<T>::remove(&dest);
+			Self::deposit_event(Event::DestinationDisallowed { dest });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Whether assets can be sent to the chain `dest`.
+		pub fn is_allowed(dest: &MultiLocation) -> bool {
+			AllowedDestinations::<T>::contains_key(dest)
+		}
+
+		/// The weight of a transfer, including the execution of its program on this chain.
+		pub fn weight_of_transfer(
+			asset: &VersionedMultiAsset,
+			fee: &VersionedMultiAsset,
+			dest: &VersionedMultiLocation,
+			dest_weight_limit: &WeightLimit,
+		) -> Weight {
+			let program = match (
+				MultiAsset::try_from(asset.clone()),
+				MultiAsset::try_from(fee.clone()),
+				MultiLocation::try_from(dest.clone()),
+			) {
+				(Ok(asset), Ok(fee), Ok(dest)) =>
+					Self::transfer_program(asset, fee, dest, dest_weight_limit.clone()).ok(),
+				_ => None,
+			};
+			// Transfers whose program cannot be built or weighed fail before executing anything.
+			let execution = program
+				.and_then(|(mut program, _)| T::Weigher::weight(&mut program).ok())
+				.unwrap_or(0);
+			execution.saturating_add(T::DbWeight::get().reads(1))
+		}
+
+		fn do_transfer(
+			sender: T::AccountId,
+			asset: MultiAsset,
+			fee: MultiAsset,
+			dest: MultiLocation,
+			dest_weight_limit: WeightLimit,
+		) -> DispatchResult {
+			let (chain, _) = Self::split_dest(dest.clone())?;
+			ensure!(Self::is_allowed(&chain), Error::<T>::DestinationNotAllowed);
+
+			let (mut program, assets) =
+				Self::transfer_program(asset, fee.clone(), dest.clone(), dest_weight_limit)?;
+			let weight =
+				T::Weigher::weight(&mut program).map_err(|()| Error::<T>::UnweighableMessage)?;
+			let origin = T::AccountIdToMultiLocation::convert(sender.clone());
+			T::XcmExecutor::execute_xcm_in_credit(origin, program, weight, weight)
+				.ensure_complete()
+				.map_err(|_| Error::<T>::XcmExecutionFailed)?;
+
+			Self::deposit_event(Event::Transferred { sender, assets, fee, dest });
+			Ok(())
+		}
+
+		/// The program sending `asset` to `dest` and paying for execution there with `fee`, and
+		/// the assets it sends.
+		fn transfer_program(
+			asset: MultiAsset,
+			fee: MultiAsset,
+			dest: MultiLocation,
+			dest_weight_limit: WeightLimit,
+		) -> Result<(Xcm<CallOf<T>>, MultiAssets), DispatchError> {
+			let (chain, beneficiary) = Self::split_dest(dest)?;
+			let reserve = Self::reserve_of(&asset).ok_or(Error::<T>::InvalidAsset)?;
+			let fee_reserve = Self::reserve_of(&fee).ok_or(Error::<T>::InvalidAsset)?;
+			ensure!(reserve == fee_reserve, Error::<T>::DistinctReserveForAssetAndFee);
+
+			let fees = fee
+				.clone()
+				.reanchored(&chain, &T::LocationInverter::ancestry())
+				.map_err(|_| Error::<T>::InvalidDest)?;
+			// Pushing the same asset twice would add the amounts up and send the fee twice.
+			let mut assets = MultiAssets::from(asset.clone());
+			if fee.id == asset.id {
+				ensure!(fee.fun <= asset.fun, Error::<T>::FeeTooHigh);
+			} else {
+				assets.push(fee);
+			}
+			let remote = Xcm(vec![
+				BuyExecution { fees, weight_limit: dest_weight_limit },
+				DepositAsset {
+					assets: Wild(All),
+					max_assets: assets.len() as u32,
+					beneficiary: beneficiary.into(),
+				},
+			]);
+
+			let program = if reserve == MultiLocation::here() {
+				Xcm(vec![TransferReserveAsset { assets: assets.clone(), dest: chain, xcm: remote }])
+			} else if reserve == MultiLocation::parent() {
+				// The barrier of the runtime denies programs making reserve transfers to the relay
+				// chain, so the transfer could never execute.
+				return Err(Error::<T>::UnsupportedReserve.into())
+			} else if reserve == chain {
+				Xcm(vec![
+					WithdrawAsset(assets.clone()),
+					InitiateReserveWithdraw {
+						assets: Definite(assets.clone()),
+						reserve: chain,
+						xcm: remote,
+					},
+				])
+			} else {
+				return Err(Error::<T>::UnsupportedReserve.into())
+			};
+			Ok((program, assets))
+		}
+
+		/// Splits `dest` into the chain and the account on that chain.
+		fn split_dest(dest: MultiLocation) -> Result<(MultiLocation, Junction), DispatchError> {
+			match dest.split_last_interior() {
+				(chain, Some(beneficiary)) if chain != MultiLocation::here() =>
+					Ok((chain, beneficiary)),
+				_ => Err(Error::<T>::InvalidDest.into()),
+			}
+		}
+
+		/// The location of the chain holding the reserve of `asset`, as seen from this chain.
+		fn reserve_of(asset: &MultiAsset) -> Option<MultiLocation> {
+			let location = match (&asset.id, &asset.fun) {
+				(Concrete(location), Fungible(amount)) if *amount > 0 => location,
+				_ => return None,
+			};
+			match (location.parents, location.first_interior()) {
+				(0, _) => Some(MultiLocation::here()),
+				(1, Some(Parachain(id))) => Some(MultiLocation::new(1, X1(Parachain(*id)))),
+				(1, _) => Some(MultiLocation::parent()),
+				_ => None,
+			}
+		}
+	}
+
+	/// Limits the reserve transfers of the XCM pallet to the allowed destinations.
+	impl<T: Config> Contains<(MultiLocation, Vec<MultiAsset>)> for Pallet<T> {
+		fn contains((dest, _): &(MultiLocation, Vec<MultiAsset>)) -> bool {
+			Self::is_allowed(dest)
+		}
+	}
+}
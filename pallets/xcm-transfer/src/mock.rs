@@ -0,0 +1,170 @@
+use crate as pallet_xcm_transfer;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU128, ConstU32, Everything},
+	weights::Weight,
+};
+use frame_system::{self as system, EnsureRoot};
+use selendra_parachain::primitives::Sibling;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, Convert, IdentityLookup},
+	AccountId32,
+};
+use std::cell::RefCell;
+use xcm::latest::prelude::*;
+use xcm_builder::{
+	AccountId32Aliases, CurrencyAdapter, FixedWeightBounds, IsConcrete, LocationInverter,
+	SiblingParachainConvertsVia, TakeWeightCredit,
+};
+use xcm_executor::XcmExecutor;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		XcmTransfer: pallet_xcm_transfer::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u128>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u128;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU128<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
+pub const BOB: AccountId32 = AccountId32::new([2u8; 32]);
+
+/// The parachain id of the mock chain.
+pub const PARA_ID: u32 = 2000;
+
+/// The parachain id of the sibling assets are sent to.
+pub const SIBLING_ID: u32 = 2001;
+
+parameter_types! {
+	pub const AnyNetwork: NetworkId = NetworkId::Any;
+	pub const HereLocation: MultiLocation = MultiLocation::here();
+	pub Ancestry: MultiLocation = Parachain(PARA_ID).into();
+	pub const UnitWeightCost: Weight = 10;
+	pub const MaxInstructions: u32 = 100;
+}
+
+pub type LocationToAccountId = (
+	SiblingParachainConvertsVia<Sibling, AccountId32>,
+	AccountId32Aliases<AnyNetwork, AccountId32>,
+);
+
+pub type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+
+thread_local! {
+	static SENT_XCM: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+}
+
+/// The messages sent to other chains.
+pub fn sent_xcm() -> Vec<(MultiLocation, Xcm<()>)> {
+	SENT_XCM.with(|sent| sent.borrow().clone())
+}
+
+/// Records the messages sent to other chains.
+pub struct TestSendXcm;
+impl SendXcm for TestSendXcm {
+	fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+		SENT_XCM.with(|sent| sent.borrow_mut().push((dest.into(), message)));
+		Ok(())
+	}
+}
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type Call = Call;
+	type XcmSender = TestSendXcm;
+	type AssetTransactor =
+		CurrencyAdapter<Balances, IsConcrete<HereLocation>, LocationToAccountId, AccountId32, ()>;
+	type OriginConverter = ();
+	type IsReserve = ();
+	type IsTeleporter = ();
+	type LocationInverter = LocationInverter<Ancestry>;
+	type Barrier = TakeWeightCredit;
+	type Weigher = Weigher;
+	type Trader = ();
+	type ResponseHandler = ();
+	type AssetTrap = ();
+	type AssetClaims = ();
+	type SubscriptionService = ();
+}
+
+pub struct AccountIdToMultiLocation;
+impl Convert<AccountId32, MultiLocation> for AccountIdToMultiLocation {
+	fn convert(account: AccountId32) -> MultiLocation {
+		Junction::AccountId32 { network: NetworkId::Any, id: account.into() }.into()
+	}
+}
+
+impl pallet_xcm_transfer::Config for Test {
+	type Event = Event;
+	type AccountIdToMultiLocation = AccountIdToMultiLocation;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type Weigher = Weigher;
+	type LocationInverter = LocationInverter<Ancestry>;
+	type DestinationOrigin = EnsureRoot<AccountId32>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(ALICE, 1_000)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
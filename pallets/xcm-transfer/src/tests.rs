@@ -0,0 +1,200 @@
+use crate::{mock::*, Error, Event as XcmTransferEvent};
+use frame_support::{assert_noop, assert_ok, traits::Contains};
+use sp_runtime::DispatchError;
+use xcm::{latest::prelude::*, VersionedMultiAsset, VersionedMultiLocation};
+use xcm_executor::traits::Convert;
+
+fn sibling() -> MultiLocation {
+	MultiLocation::new(1, X1(Parachain(SIBLING_ID)))
+}
+
+fn bob_on(chain: MultiLocation) -> MultiLocation {
+	chain
+		.pushed_with_interior(Junction::AccountId32 { network: NetworkId::Any, id: BOB.into() })
+		.unwrap()
+}
+
+/// `amount` of KMD as seen from this chain.
+fn kmd(amount: u128) -> MultiAsset {
+	(MultiLocation::here(), amount).into()
+}
+
+/// `amount` of KMD as seen from the sibling.
+fn kmd_on_sibling(amount: u128) -> MultiAsset {
+	(MultiLocation::new(1, X1(Parachain(PARA_ID))), amount).into()
+}
+
+fn allow_sibling() {
+	assert_ok!(XcmTransfer::allow_destination(
+		Origin::root(),
+		Box::new(VersionedMultiLocation::V1(sibling()))
+	));
+}
+
+fn transfer(asset: MultiAsset, dest: MultiLocation) -> sp_runtime::DispatchResult {
+	XcmTransfer::transfer(
+		Origin::signed(ALICE),
+		Box::new(VersionedMultiAsset::V1(asset)),
+		Box::new(VersionedMultiLocation::V1(dest)),
+		Limited(1_000),
+	)
+}
+
+#[test]
+fn kmd_is_sent_to_the_sovereign_account_of_the_destination() {
+	new_test_ext().execute_with(|| {
+		allow_sibling();
+		assert_ok!(transfer(kmd(500), bob_on(sibling())));
+
+		let sovereign = LocationToAccountId::convert(sibling()).unwrap();
+		assert_eq!(Balances::free_balance(&ALICE), 500);
+		assert_eq!(Balances::free_balance(&sovereign), 500);
+		assert_eq!(
+			sent_xcm(),
+			vec![(
+				sibling(),
+				Xcm(vec![
+					ReserveAssetDeposited(kmd_on_sibling(500).into()),
+					ClearOrigin,
+					BuyExecution { fees: kmd_on_sibling(500), weight_limit: Limited(1_000) },
+					DepositAsset {
+						assets: Wild(All),
+						max_assets: 1,
+						beneficiary: bob_on(MultiLocation::here()),
+					},
+				])
+			)]
+		);
+		System::assert_last_event(
+			XcmTransferEvent::Transferred {
+				sender: ALICE,
+				assets: kmd(500).into(),
+				fee: kmd(500),
+				dest: bob_on(sibling()),
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn execution_can_be_paid_with_a_separate_fee() {
+	new_test_ext().execute_with(|| {
+		allow_sibling();
+		assert_ok!(XcmTransfer::transfer_with_fee(
+			Origin::signed(ALICE),
+			Box::new(VersionedMultiAsset::V1(kmd(500))),
+			Box::new(VersionedMultiAsset::V1(kmd(100))),
+			Box::new(VersionedMultiLocation::V1(bob_on(sibling()))),
+			Unlimited,
+		));
+
+		// A fee in the sent asset is taken out of it.
+		assert_eq!(Balances::free_balance(&ALICE), 500);
+		assert_eq!(
+			sent_xcm()[0].1 .0[..3],
+			[
+				ReserveAssetDeposited(kmd_on_sibling(500).into()),
+				ClearOrigin,
+				BuyExecution { fees: kmd_on_sibling(100), weight_limit: Unlimited },
+			]
+		);
+
+		assert_noop!(
+			XcmTransfer::transfer_with_fee(
+				Origin::signed(ALICE),
+				Box::new(VersionedMultiAsset::V1(kmd(100))),
+				Box::new(VersionedMultiAsset::V1(kmd(200))),
+				Box::new(VersionedMultiLocation::V1(bob_on(sibling()))),
+				Unlimited,
+			),
+			Error::<Test>::FeeTooHigh
+		);
+	});
+}
+
+#[test]
+fn fee_must_have_the_same_reserve() {
+	new_test_ext().execute_with(|| {
+		allow_sibling();
+		assert_noop!(
+			XcmTransfer::transfer_with_fee(
+				Origin::signed(ALICE),
+				Box::new(VersionedMultiAsset::V1(kmd(500))),
+				Box::new(VersionedMultiAsset::V1((MultiLocation::parent(), 100).into())),
+				Box::new(VersionedMultiLocation::V1(bob_on(sibling()))),
+				Unlimited,
+			),
+			Error::<Test>::DistinctReserveForAssetAndFee
+		);
+	});
+}
+
+#[test]
+fn foreign_assets_can_only_go_back_to_their_reserve() {
+	new_test_ext().execute_with(|| {
+		allow_sibling();
+		assert_noop!(
+			transfer((MultiLocation::parent(), 100).into(), bob_on(sibling())),
+			Error::<Test>::UnsupportedReserve
+		);
+	});
+}
+
+#[test]
+fn the_relay_chain_token_cannot_be_sent_back() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(XcmTransfer::allow_destination(
+			Origin::root(),
+			Box::new(VersionedMultiLocation::V1(MultiLocation::parent()))
+		));
+		assert_noop!(
+			transfer((MultiLocation::parent(), 100).into(), bob_on(MultiLocation::parent())),
+			Error::<Test>::UnsupportedReserve
+		);
+		assert!(sent_xcm().is_empty());
+	});
+}
+
+#[test]
+fn invalid_transfers_are_rejected() {
+	new_test_ext().execute_with(|| {
+		allow_sibling();
+		assert_noop!(transfer(kmd(0), bob_on(sibling())), Error::<Test>::InvalidAsset);
+		assert_noop!(transfer(kmd(500), bob_on(MultiLocation::here())), Error::<Test>::InvalidDest);
+		assert_noop!(transfer(kmd(500), sibling()), Error::<Test>::DestinationNotAllowed);
+		assert_noop!(transfer(kmd(2_000), bob_on(sibling())), Error::<Test>::XcmExecutionFailed);
+		assert!(sent_xcm().is_empty());
+	});
+}
+
+#[test]
+fn governance_sets_the_allowed_destinations() {
+	new_test_ext().execute_with(|| {
+		let reserve_transfer = (sibling(), vec![kmd(500)]);
+		assert_noop!(transfer(kmd(500), bob_on(sibling())), Error::<Test>::DestinationNotAllowed);
+		assert!(!XcmTransfer::contains(&reserve_transfer));
+
+		assert_noop!(
+			XcmTransfer::allow_destination(
+				Origin::signed(ALICE),
+				Box::new(VersionedMultiLocation::V1(sibling()))
+			),
+			DispatchError::BadOrigin
+		);
+		allow_sibling();
+		System::assert_last_event(XcmTransferEvent::DestinationAllowed { dest: sibling() }.into());
+		assert!(XcmTransfer::contains(&reserve_transfer));
+		assert_ok!(transfer(kmd(500), bob_on(sibling())));
+
+		assert_ok!(XcmTransfer::disallow_destination(
+			Origin::root(),
+			Box::new(VersionedMultiLocation::V1(sibling()))
+		));
+		System::assert_last_event(
+			XcmTransferEvent::DestinationDisallowed { dest: sibling() }.into(),
+		);
+		assert!(!XcmTransfer::contains(&reserve_transfer));
+		assert_noop!(transfer(kmd(500), bob_on(sibling())), Error::<Test>::DestinationNotAllowed);
+	});
+}
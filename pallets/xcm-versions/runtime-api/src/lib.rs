@@ -0,0 +1,17 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime APIs of the XCM versions used for other chains.
+
+use xcm::{Version as XcmVersion, VersionedMultiLocation};
+
+sp_api::decl_runtime_apis! {
+	/// The XCM versions messages to other chains are sent in.
+	pub trait XcmVersionsApi {
+		/// The XCM version messages to `dest` are sent in, or `None` if its version is unknown
+		/// and there is no default version.
+		fn version_for(dest: VersionedMultiLocation) -> Option<XcmVersion>;
+
+		/// The latest XCM version supported by the chain.
+		fn current_version() -> XcmVersion;
+	}
+}
@@ -0,0 +1,101 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Inspecting and forcing the XCM versions used for other chains.
+//!
+//! Messages to other chains are wrapped by the XCM pallet in the latest version the destination
+//! is known to support, which it learns by subscribing to version notifications. Until a
+//! destination answers, the default version is used, and destinations with neither are
+//! unreachable. This pallet lets governance force the version of a destination that does not
+//! answer, or the default version, and reports the version used for every destination.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::{pallet_prelude::*, RawOrigin};
+	use sp_std::{boxed::Box, vec};
+	use xcm::{
+		latest::{prelude::*, VERSION as XCM_VERSION},
+		Version as XcmVersion, VersionedMultiLocation, VersionedXcm, WrapVersion,
+	};
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_xcm::Config {
+		/// The origin allowed to force XCM versions.
+		type AdminOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The location uses an XCM version that cannot be converted.
+		BadVersion,
+		/// The XCM version is newer than the latest version supported by the chain.
+		UnsupportedVersion,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Send messages to `dest` in XCM `version`, whatever version it advertises.
+		///
+		/// A later version notification from `dest` overrides the forced version.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn force_version(
+			origin: OriginFor<T>,
+			dest: Box<VersionedMultiLocation>,
+			version: XcmVersion,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let dest = MultiLocation::try_from(*dest).map_err(|()| Error::<T>::BadVersion)?;
+			ensure!(version <= XCM_VERSION, Error::<T>::UnsupportedVersion);
+
+			pallet_xcm::Pallet::<T>::force_xcm_version(
+				RawOrigin::Root.into(),
+				Box::new(dest),
+				version,
+			)
+		}
+
+		/// Send messages to destinations whose version is unknown in XCM `version`, or stop
+		/// sending them messages with `None`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn force_default_version(
+			origin: OriginFor<T>,
+			version: Option<XcmVersion>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(version.map_or(true, |v| v <= XCM_VERSION), Error::<T>::UnsupportedVersion);
+
+			pallet_xcm::Pallet::<T>::force_default_xcm_version(RawOrigin::Root.into(), version)
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The XCM version messages to `dest` are sent in, or `None` if they cannot be sent.
+		///
+		/// The XCM pallet does not expose the versions it knows, so this wraps a message every
+		/// version can express and reads the version it was wrapped in. Wrapping for an unknown
+		/// destination queues it for version discovery, so it is meant for runtime APIs rather
+		/// than extrinsics.
+		pub fn version_for(dest: &MultiLocation) -> Option<XcmVersion> {
+			let probe = Xcm::<()>(vec![WithdrawAsset(MultiAssets::new())]);
+			match <pallet_xcm::Pallet<T> as WrapVersion>::wrap_version(dest, probe).ok()? {
+				VersionedXcm::V0(_) => Some(0),
+				VersionedXcm::V1(_) => Some(1),
+				VersionedXcm::V2(_) => Some(2),
+			}
+		}
+	}
+}
@@ -0,0 +1,144 @@
+use crate as pallet_xcm_versions;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU32, Everything, Nothing},
+	weights::Weight,
+};
+use frame_system::{self as system, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+use xcm::{latest::prelude::*, VersionedXcm, WrapVersion};
+use xcm_builder::{EnsureXcmOrigin, FixedWeightBounds, LocationInverter, TakeWeightCredit};
+use xcm_executor::XcmExecutor;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		SelendraXcm: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
+		XcmVersions: pallet_xcm_versions::{Pallet, Call},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+/// The parachain id of the mock chain.
+pub const PARA_ID: u32 = 2000;
+
+parameter_types! {
+	pub Ancestry: MultiLocation = Parachain(PARA_ID).into();
+	pub const UnitWeightCost: Weight = 10;
+	pub const MaxInstructions: u32 = 100;
+}
+
+pub type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+
+thread_local! {
+	static SENT_XCM: RefCell<Vec<(MultiLocation, VersionedXcm<()>)>> = RefCell::new(Vec::new());
+}
+
+/// The messages sent to other chains, in the version they were sent in.
+pub fn sent_xcm() -> Vec<(MultiLocation, VersionedXcm<()>)> {
+	SENT_XCM.with(|sent| sent.borrow().clone())
+}
+
+/// Wraps messages for their destination like the XCMP queue does, and records them.
+pub struct TestSendXcm;
+impl SendXcm for TestSendXcm {
+	fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+		let dest = dest.into();
+		let message = <SelendraXcm as WrapVersion>::wrap_version(&dest, message)
+			.map_err(|()| SendError::DestinationUnsupported)?;
+		SENT_XCM.with(|sent| sent.borrow_mut().push((dest, message)));
+		Ok(())
+	}
+}
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type Call = Call;
+	type XcmSender = TestSendXcm;
+	type AssetTransactor = ();
+	type OriginConverter = ();
+	type IsReserve = ();
+	type IsTeleporter = ();
+	type LocationInverter = LocationInverter<Ancestry>;
+	type Barrier = TakeWeightCredit;
+	type Weigher = Weigher;
+	type Trader = ();
+	type ResponseHandler = SelendraXcm;
+	type AssetTrap = SelendraXcm;
+	type AssetClaims = SelendraXcm;
+	type SubscriptionService = SelendraXcm;
+}
+
+impl pallet_xcm::Config for Test {
+	type Event = Event;
+	type SendXcmOrigin = EnsureXcmOrigin<Origin, ()>;
+	type XcmRouter = TestSendXcm;
+	type ExecuteXcmOrigin = EnsureXcmOrigin<Origin, ()>;
+	type XcmExecuteFilter = Nothing;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmTeleportFilter = Nothing;
+	type XcmReserveTransferFilter = Nothing;
+	type Weigher = Weigher;
+	type LocationInverter = LocationInverter<Ancestry>;
+	type Origin = Origin;
+	type Call = Call;
+
+	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
+	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+}
+
+impl pallet_xcm_versions::Config for Test {
+	type AdminOrigin = EnsureRoot<u64>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,159 @@
+use crate::{mock::*, Error};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+use xcm::{latest::prelude::*, v1, VersionedMultiLocation, VersionedXcm};
+
+fn sibling(para_id: u32) -> MultiLocation {
+	MultiLocation::new(1, X1(Parachain(para_id)))
+}
+
+fn bob() -> MultiLocation {
+	Junction::AccountId32 { network: NetworkId::Any, id: [2u8; 32] }.into()
+}
+
+/// `amount` of KMD as seen from a sibling.
+fn kmd(amount: u128) -> MultiAsset {
+	(sibling(PARA_ID), amount).into()
+}
+
+/// The message of a reserve transfer of KMD, which every version can express.
+fn reserve_transfer() -> Xcm<()> {
+	Xcm(vec![
+		ReserveAssetDeposited(kmd(500).into()),
+		ClearOrigin,
+		BuyExecution { fees: kmd(500), weight_limit: Limited(1_000) },
+		DepositAsset { assets: Wild(All), max_assets: 1, beneficiary: bob() },
+	])
+}
+
+fn force_version(dest: MultiLocation, version: u32) {
+	assert_ok!(XcmVersions::force_version(
+		Origin::root(),
+		Box::new(VersionedMultiLocation::V1(dest)),
+		version
+	));
+}
+
+fn send(dest: MultiLocation, message: Xcm<()>) -> Result<(), SendError> {
+	SelendraXcm::send_xcm(Here, dest, message)
+}
+
+#[test]
+fn messages_to_siblings_on_the_latest_version_are_not_downgraded() {
+	new_test_ext().execute_with(|| {
+		force_version(sibling(2001), 2);
+		assert_eq!(XcmVersions::version_for(&sibling(2001)), Some(2));
+
+		assert_ok!(send(sibling(2001), reserve_transfer()));
+		assert_eq!(sent_xcm(), vec![(sibling(2001), VersionedXcm::V2(reserve_transfer()))]);
+	});
+}
+
+#[test]
+fn messages_to_older_siblings_are_downgraded() {
+	new_test_ext().execute_with(|| {
+		force_version(sibling(2001), 1);
+		assert_eq!(XcmVersions::version_for(&sibling(2001)), Some(1));
+		System::assert_last_event(
+			pallet_xcm::Event::SupportedVersionChanged(sibling(2001), 1).into(),
+		);
+
+		assert_ok!(send(sibling(2001), reserve_transfer()));
+		assert_eq!(
+			sent_xcm(),
+			vec![(
+				sibling(2001),
+				VersionedXcm::V1(v1::Xcm::ReserveAssetDeposited {
+					assets: kmd(500).into(),
+					effects: vec![
+						v1::Order::BuyExecution {
+							fees: kmd(500),
+							weight: 0,
+							debt: 1_000,
+							halt_on_error: true,
+							instructions: vec![],
+						},
+						v1::Order::DepositAsset {
+							assets: Wild(All),
+							max_assets: 1,
+							beneficiary: bob(),
+						},
+					],
+				})
+			)]
+		);
+	});
+}
+
+#[test]
+fn messages_older_siblings_cannot_express_are_not_sent() {
+	new_test_ext().execute_with(|| {
+		force_version(sibling(2001), 1);
+
+		assert_eq!(send(sibling(2001), Xcm(vec![Trap(1)])), Err(SendError::DestinationUnsupported));
+		assert!(sent_xcm().is_empty());
+	});
+}
+
+#[test]
+fn unknown_destinations_use_the_default_version() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(XcmVersions::version_for(&sibling(2001)), None);
+		assert_eq!(send(sibling(2001), reserve_transfer()), Err(SendError::DestinationUnsupported));
+
+		assert_ok!(XcmVersions::force_default_version(Origin::root(), Some(1)));
+		assert_eq!(XcmVersions::version_for(&sibling(2001)), Some(1));
+		assert_ok!(send(sibling(2001), reserve_transfer()));
+		assert!(matches!(sent_xcm()[..], [(_, VersionedXcm::V1(_))]));
+
+		assert_ok!(XcmVersions::force_default_version(Origin::root(), None));
+		assert_eq!(XcmVersions::version_for(&sibling(2001)), None);
+	});
+}
+
+#[test]
+fn known_versions_take_precedence_over_the_default_version() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(XcmVersions::force_default_version(Origin::root(), Some(1)));
+		force_version(sibling(2001), 2);
+
+		assert_eq!(XcmVersions::version_for(&sibling(2001)), Some(2));
+		assert_eq!(XcmVersions::version_for(&sibling(2002)), Some(1));
+	});
+}
+
+#[test]
+fn versions_newer_than_the_current_version_cannot_be_forced() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			XcmVersions::force_version(
+				Origin::root(),
+				Box::new(VersionedMultiLocation::V1(sibling(2001))),
+				3
+			),
+			Error::<Test>::UnsupportedVersion
+		);
+		assert_noop!(
+			XcmVersions::force_default_version(Origin::root(), Some(3)),
+			Error::<Test>::UnsupportedVersion
+		);
+	});
+}
+
+#[test]
+fn only_the_admin_origin_can_force_versions() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			XcmVersions::force_version(
+				Origin::signed(1),
+				Box::new(VersionedMultiLocation::V1(sibling(2001))),
+				1
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			XcmVersions::force_default_version(Origin::signed(1), Some(1)),
+			DispatchError::BadOrigin
+		);
+	});
+}
@@ -16,6 +16,9 @@
 pub use opaque::*;
 pub use types::*;
 
+pub mod plot;
+pub mod storage;
+
 /// Common types of parachains.
 mod types {
 	use sp_runtime::traits::{IdentifyAccount, Verify};
@@ -37,6 +40,9 @@ mod types {
 	/// Balance of an account.
 	pub type Balance = u128;
 
+	/// Identifier of a fungible asset other than the native token.
+	pub type AssetId = u32;
+
 	/// Index of a transaction in the chain.
 	pub type Index = u32;
 
@@ -0,0 +1,259 @@
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Plot layout shared by the farmer and the runtime.
+//!
+//! A plot is a sequence of fixed size pieces, each made of `CHUNKS` chunks. Every piece is derived
+//! from the farmer's account, so the runtime can recompute any piece on its own. Pieces are sealed
+//! by a hash chain: every chunk is the previous one hashed `SEAL_ROUNDS` times, so a chunk can
+//! only be computed after all the chunks before it.
+//!
+//! A challenge audits the chunk at one offset of every piece. The farmer answers with the pieces
+//! whose audited chunk scores under a target set so that a whole plot holds about
+//! `EXPECTED_SOLUTIONS` of them, and has to find at least `SOLUTIONS`. A farmer keeping the plot
+//! reads one chunk per piece. A farmer that did not keep it has to reseal about half of every
+//! piece of the plot for each challenge, which costs far more than the disk it saves.
+
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
+
+/// Size of a single plot piece in bytes.
+pub const PIECE_SIZE: usize = 4096;
+
+/// Size of a chunk, the part of a piece a challenge audits, in bytes.
+pub const CHUNK_SIZE: usize = 32;
+
+/// Number of chunks in a piece.
+pub const CHUNKS: u32 = (PIECE_SIZE / CHUNK_SIZE) as u32;
+
+/// Number of hashes sealing every chunk into the next one.
+pub const SEAL_ROUNDS: u32 = 8;
+
+/// Number of solutions a challenge has to be answered with.
+pub const SOLUTIONS: u32 = 16;
+
+/// Number of solutions a whole plot holds on average. It is twice `SOLUTIONS`, so a farmer
+/// keeping its plot misses a challenge about once in 1500, while a farmer keeping half of it
+/// misses about every other challenge.
+pub const EXPECTED_SOLUTIONS: u64 = 32;
+
+/// Domain separator for the plot seed.
+const PLOT_SEED_CONTEXT: &[u8] = b"kumandra/plot";
+
+/// Domain separator for the first chunk of a piece.
+const PIECE_CONTEXT: &[u8] = b"kumandra/piece";
+
+/// Domain separator for the audited offset.
+const CHALLENGE_CONTEXT: &[u8] = b"kumandra/challenge";
+
+/// Domain separator for audit tags.
+const AUDIT_CONTEXT: &[u8] = b"kumandra/audit";
+
+/// The seed every piece of a farmer's plot is derived from.
+pub fn plot_seed(farmer: &[u8]) -> H256 {
+	blake2_256(&[PLOT_SEED_CONTEXT, farmer].concat()).into()
+}
+
+/// Seal `chunk` into the next chunk of its piece.
+fn seal(chunk: [u8; CHUNK_SIZE]) -> [u8; CHUNK_SIZE] {
+	(0..SEAL_ROUNDS).fold(chunk, |chunk, _| blake2_256(&chunk))
+}
+
+/// The first chunk of the piece at `index` of the plot derived from `seed`.
+fn first_chunk(seed: &H256, index: u64) -> [u8; CHUNK_SIZE] {
+	seal(blake2_256(&[PIECE_CONTEXT, seed.as_bytes(), &index.to_le_bytes()[..]].concat()))
+}
+
+/// Encode the piece at `index` of the plot derived from `seed`.
+pub fn encode_piece(seed: &H256, index: u64) -> Vec<u8> {
+	let mut piece = Vec::with_capacity(PIECE_SIZE);
+	let mut chunk = first_chunk(seed, index);
+	piece.extend_from_slice(&chunk);
+	for _ in 1..CHUNKS {
+		chunk = seal(chunk);
+		piece.extend_from_slice(&chunk);
+	}
+	piece
+}
+
+/// Encode the chunk at `offset` of the piece at `index`, without the chunks after it.
+pub fn encode_chunk(seed: &H256, index: u64, offset: u32) -> [u8; CHUNK_SIZE] {
+	(0..offset).fold(first_chunk(seed, index), |chunk, _| seal(chunk))
+}
+
+/// The offset of the chunks audited by a challenge of `farmer`.
+pub fn audit_offset(randomness: &[u8], farmer: &[u8]) -> u32 {
+	let hash = blake2_256(&[CHALLENGE_CONTEXT, randomness, farmer].concat());
+	let mut bytes = [0u8; 4];
+	bytes.copy_from_slice(&hash[..4]);
+	u32::from_le_bytes(bytes) % CHUNKS
+}
+
+/// The score of an audited chunk. The chunk is a solution if its tag is at most the target.
+pub fn audit_tag(randomness: &[u8], chunk: &[u8]) -> u64 {
+	let hash = blake2_256(&[AUDIT_CONTEXT, randomness, chunk].concat());
+	let mut bytes = [0u8; 8];
+	bytes.copy_from_slice(&hash[..8]);
+	u64::from_le_bytes(bytes)
+}
+
+/// The highest tag of a solution in a plot of `pieces` pieces.
+pub fn audit_target(pieces: u64) -> u64 {
+	(u64::MAX / pieces.max(1)).saturating_mul(EXPECTED_SOLUTIONS)
+}
+
+/// The number of solutions a challenge of a plot of `pieces` pieces has to be answered with.
+/// Plots smaller than `SOLUTIONS` answer with every piece.
+pub fn required_solutions(pieces: u64) -> u32 {
+	u32::try_from(pieces).map_or(SOLUTIONS, |pieces| pieces.min(SOLUTIONS))
+}
+
+/// Find the solutions of a challenge among the `chunks` audited in every piece of a plot, in the
+/// order of the pieces.
+///
+/// Returns the indices of the first `required_solutions` pieces whose chunk is a solution, or
+/// fewer if the plot does not hold enough of them.
+pub fn find_solutions(
+	randomness: &[u8],
+	pieces: u64,
+	chunks: impl IntoIterator<Item = [u8; CHUNK_SIZE]>,
+) -> Vec<u64> {
+	let target = audit_target(pieces);
+	chunks
+		.into_iter()
+		.zip(0..pieces)
+		.filter(|(chunk, _)| audit_tag(randomness, chunk) <= target)
+		.map(|(_, index)| index)
+		.take(required_solutions(pieces) as usize)
+		.collect()
+}
+
+/// Check that `solutions` answer the challenge seeded by `randomness` of the plot of `pieces`
+/// pieces derived from `seed`.
+///
+/// The solutions have to be distinct pieces of the plot, listed in ascending order.
+pub fn verify_solutions(
+	seed: &H256,
+	randomness: &[u8],
+	offset: u32,
+	pieces: u64,
+	solutions: &[u64],
+) -> bool {
+	let target = audit_target(pieces);
+	solutions.len() == required_solutions(pieces) as usize &&
+		solutions.windows(2).all(|pair| pair[0] < pair[1]) &&
+		solutions.last().map_or(true, |last| *last < pieces) &&
+		solutions
+			.iter()
+			.all(|index| audit_tag(randomness, &encode_chunk(seed, *index, offset)) <= target)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RANDOMNESS: [u8; 32] = [9u8; 32];
+
+	fn solutions(seed: &H256, pieces: u64, offset: u32) -> Vec<u64> {
+		let chunks = (0..pieces).map(|index| encode_chunk(seed, index, offset));
+		find_solutions(&RANDOMNESS, pieces, chunks)
+	}
+
+	#[test]
+	fn pieces_are_deterministic_and_full_size() {
+		let seed = plot_seed(&[1u8; 32]);
+		let piece = encode_piece(&seed, 7);
+		assert_eq!(piece.len(), PIECE_SIZE);
+		assert_eq!(piece, encode_piece(&seed, 7));
+		assert_ne!(piece, encode_piece(&seed, 8));
+		assert_ne!(piece, encode_piece(&plot_seed(&[2u8; 32]), 7));
+	}
+
+	#[test]
+	fn chunks_are_the_sealed_chain_of_their_piece() {
+		let seed = plot_seed(&[1u8; 32]);
+		let piece = encode_piece(&seed, 7);
+		for offset in 0..CHUNKS {
+			let start = offset as usize * CHUNK_SIZE;
+			assert_eq!(&piece[start..start + CHUNK_SIZE], &encode_chunk(&seed, 7, offset)[..]);
+		}
+
+		let second =
+			(0..SEAL_ROUNDS).fold(encode_chunk(&seed, 7, 0), |chunk, _| blake2_256(&chunk));
+		assert_eq!(second, encode_chunk(&seed, 7, 1));
+	}
+
+	#[test]
+	fn audit_offset_is_in_range() {
+		for byte in 0..=255u8 {
+			assert!(audit_offset(&[byte; 32], &[1u8; 32]) < CHUNKS);
+		}
+	}
+
+	#[test]
+	fn small_plots_answer_with_every_piece() {
+		let seed = plot_seed(&[1u8; 32]);
+		for pieces in [1u64, 2, 8, 16] {
+			let solutions = solutions(&seed, pieces, 3);
+			assert_eq!(solutions, (0..pieces).collect::<Vec<_>>());
+			assert!(verify_solutions(&seed, &RANDOMNESS, 3, pieces, &solutions));
+		}
+	}
+
+	#[test]
+	fn solutions_of_large_plots_verify() {
+		let seed = plot_seed(&[1u8; 32]);
+		let pieces = 1 << 10;
+		let solutions = solutions(&seed, pieces, 5);
+		assert_eq!(solutions.len(), SOLUTIONS as usize);
+		assert!(solutions.iter().all(|index| *index < pieces));
+		assert!(verify_solutions(&seed, &RANDOMNESS, 5, pieces, &solutions));
+	}
+
+	#[test]
+	fn wrong_solutions_are_rejected() {
+		let seed = plot_seed(&[1u8; 32]);
+		let pieces = 1 << 10;
+		let solutions = solutions(&seed, pieces, 5);
+		let verify = |solutions: &[u64]| verify_solutions(&seed, &RANDOMNESS, 5, pieces, solutions);
+
+		// Too few, repeated, unordered or out of the plot.
+		assert!(!verify(&solutions[1..]));
+		let mut repeated = solutions.clone();
+		repeated[1] = repeated[0];
+		assert!(!verify(&repeated));
+		let mut unordered = solutions.clone();
+		unordered.swap(0, 1);
+		assert!(!verify(&unordered));
+		let mut outside = solutions.clone();
+		*outside.last_mut().unwrap() = pieces;
+		assert!(!verify(&outside));
+
+		// Audited at another offset, in the plot of another farmer or below the target.
+		assert!(!verify_solutions(&seed, &RANDOMNESS, 6, pieces, &solutions));
+		let other = plot_seed(&[2u8; 32]);
+		assert!(!verify_solutions(&other, &RANDOMNESS, 5, pieces, &solutions));
+		let mut missed = solutions.clone();
+		let last = missed.len() - 1;
+		missed[last] = (missed[last - 1] + 1..pieces)
+			.find(|index| {
+				!solutions.contains(index) && {
+					let chunk = encode_chunk(&seed, *index, 5);
+					audit_tag(&RANDOMNESS, &chunk) > audit_target(pieces)
+				}
+			})
+			.unwrap();
+		assert!(!verify(&missed));
+	}
+}
@@ -0,0 +1,40 @@
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Interfaces between the storage pallets.
+
+use sp_std::vec::Vec;
+
+/// What the deal pallet needs to know about storage providers.
+pub trait StorageProviders<AccountId> {
+	/// Whether `who` currently accepts new deals.
+	fn accepts_deals(who: &AccountId) -> bool;
+
+	/// The capacity, in bytes, `who` currently has proven.
+	fn proven_capacity(who: &AccountId) -> u64;
+
+	/// The region and the operator `who` declared, if any.
+	fn profile(who: &AccountId) -> Option<(Vec<u8>, AccountId)>;
+}
+
+/// Accounts whose on-chain identity was verified.
+pub trait VerifiedIdentities<AccountId> {
+	/// Whether `who` has a verified identity.
+	fn is_verified(who: &AccountId) -> bool;
+}
+
+impl<AccountId> VerifiedIdentities<AccountId> for () {
+	fn is_verified(_: &AccountId) -> bool {
+		false
+	}
+}
@@ -0,0 +1,49 @@
+//! Custom origins for governance interventions.
+
+pub use pallet_custom_origins::*;
+
+#[frame_support::pallet]
+pub mod pallet_custom_origins {
+	use frame_support::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	/// The origins referenda on the non-root tracks dispatch with.
+	#[derive(PartialEq, Eq, Clone, MaxEncodedLen, Encode, Decode, TypeInfo, RuntimeDebug)]
+	#[pallet::origin]
+	pub enum Origin {
+		/// Origin able to manage the collator set.
+		CollatorAdmin,
+		/// Origin able to administer the XCM queues.
+		XcmAdmin,
+		/// Origin able to spend from the treasury.
+		Treasurer,
+		/// Origin able to authorize runtime upgrades.
+		RuntimeUpgrader,
+	}
+
+	macro_rules! decl_unit_ensures {
+		( $( $name:ident ),* ) => { $(
+			#[doc = concat!("Ensures the origin is `Origin::", stringify!($name), "`.")]
+			pub struct $name;
+			impl<O: Into<Result<Origin, O>> + From<Origin>> EnsureOrigin<O> for $name {
+				type Success = ();
+				fn try_origin(o: O) -> Result<Self::Success, O> {
+					o.into().and_then(|o| match o {
+						Origin::$name => Ok(()),
+						r => Err(O::from(r)),
+					})
+				}
+				#[cfg(feature = "runtime-benchmarks")]
+				fn successful_origin() -> O {
+					O::from(Origin::$name)
+				}
+			}
+		)* };
+	}
+	decl_unit_ensures!(CollatorAdmin, XcmAdmin, Treasurer, RuntimeUpgrader);
+}
@@ -0,0 +1,114 @@
+//! Track configurations for governance.
+
+use super::*;
+
+const fn percent(x: u32) -> Perbill {
+	Perbill::from_percent(x)
+}
+
+/// Approval needed at the start of the decision period, falling linearly to `floor` at its end.
+const fn linear(floor: u32, ceil: u32) -> Curve {
+	Curve::LinearDecreasing { length: percent(100), floor: percent(floor), ceil: percent(ceil) }
+}
+
+// Root can do anything `runtime_upgrader` can, so it must never be easier to pass.
+const TRACKS_DATA: [(u16, pallet_referenda::TrackInfo<Balance, BlockNumber>); 5] = [
+	(
+		0,
+		pallet_referenda::TrackInfo {
+			name: "root",
+			max_deciding: 1,
+			decision_deposit: 10_000 * UNIT,
+			prepare_period: 2 * HOURS,
+			decision_period: 14 * DAYS,
+			confirm_period: 2 * DAYS,
+			min_enactment_period: DAYS,
+			min_approval: linear(60, 100),
+			min_support: linear(10, 50),
+		},
+	),
+	(
+		1,
+		pallet_referenda::TrackInfo {
+			name: "collator_admin",
+			max_deciding: 10,
+			decision_deposit: 1_000 * UNIT,
+			prepare_period: HOURS,
+			decision_period: 7 * DAYS,
+			confirm_period: 3 * HOURS,
+			min_enactment_period: 10 * MINUTES,
+			min_approval: linear(50, 100),
+			min_support: linear(0, 25),
+		},
+	),
+	(
+		2,
+		pallet_referenda::TrackInfo {
+			name: "xcm_admin",
+			max_deciding: 10,
+			decision_deposit: 1_000 * UNIT,
+			prepare_period: HOURS,
+			decision_period: 7 * DAYS,
+			confirm_period: 3 * HOURS,
+			min_enactment_period: 10 * MINUTES,
+			min_approval: linear(50, 100),
+			min_support: linear(0, 25),
+		},
+	),
+	(
+		3,
+		pallet_referenda::TrackInfo {
+			name: "treasurer",
+			max_deciding: 10,
+			decision_deposit: 500 * UNIT,
+			prepare_period: 2 * HOURS,
+			decision_period: 14 * DAYS,
+			confirm_period: DAYS,
+			min_enactment_period: DAYS,
+			min_approval: linear(50, 100),
+			min_support: linear(0, 25),
+		},
+	),
+	(
+		4,
+		pallet_referenda::TrackInfo {
+			name: "runtime_upgrader",
+			max_deciding: 1,
+			decision_deposit: 10_000 * UNIT,
+			prepare_period: 2 * HOURS,
+			decision_period: 14 * DAYS,
+			confirm_period: 2 * DAYS,
+			min_enactment_period: DAYS,
+			min_approval: linear(60, 100),
+			min_support: linear(10, 50),
+		},
+	),
+];
+
+pub struct TracksInfo;
+impl pallet_referenda::TracksInfo<Balance, BlockNumber> for TracksInfo {
+	type Id = u16;
+	type Origin = <Origin as frame_support::traits::OriginTrait>::PalletsOrigin;
+
+	fn tracks() -> &'static [(Self::Id, pallet_referenda::TrackInfo<Balance, BlockNumber>)] {
+		&TRACKS_DATA[..]
+	}
+
+	fn track_for(id: &Self::Origin) -> Result<Self::Id, ()> {
+		if let Ok(system_origin) = frame_system::RawOrigin::try_from(id.clone()) {
+			match system_origin {
+				frame_system::RawOrigin::Root => Ok(0),
+				_ => Err(()),
+			}
+		} else if let Ok(custom_origin) = origins::Origin::try_from(id.clone()) {
+			match custom_origin {
+				origins::Origin::CollatorAdmin => Ok(1),
+				origins::Origin::XcmAdmin => Ok(2),
+				origins::Origin::Treasurer => Ok(3),
+				origins::Origin::RuntimeUpgrader => Ok(4),
+			}
+		} else {
+			Err(())
+		}
+	}
+}
@@ -0,0 +1,189 @@
+//! Implementations of pallet traits specific to this runtime.
+
+use crate::{
+	xcm_config::{CheckingAccount, KmdPerSecond},
+	AccountId, AssetId, AssetRegistry, Assets, Authorship, Balance, Balances, Call, Identity,
+	Maintenance, Origin, Runtime, Treasury, XcmpQueue,
+};
+use frame_support::{
+	log,
+	traits::{
+		fungibles::{Balanced, CreditOf},
+		tokens::BalanceConversion,
+		Contains, Currency, Imbalance, OnUnbalanced,
+	},
+};
+use kumandra_primitive::storage::VerifiedIdentities;
+use pallet_asset_registry::AssetMetadataOf;
+use pallet_asset_registry_runtime_api::ForeignAsset;
+use pallet_asset_tx_payment::HandleCredit;
+use pallet_identity::Judgement;
+use pallet_maintenance::PauseXcmExecution;
+use selendra_runtime_common::impls::ToAuthor;
+use sp_core::U256;
+use sp_runtime::{ArithmeticError, DispatchError, DispatchResult, TokenError};
+use xcm::{latest::MultiLocation, VersionedMultiLocation};
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Splits transaction fees between the treasury and the block author. Tips go to the block author
+/// in full.
+pub struct DealWithFees;
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+		if let Some(fees) = fees_then_tips.next() {
+			// 80% of the fees go to the treasury, 20% to the block author.
+			let (to_treasury, mut to_author) = fees.ration(80, 20);
+			if let Some(tips) = fees_then_tips.next() {
+				tips.merge_into(&mut to_author);
+			}
+			Treasury::on_unbalanced(to_treasury);
+			ToAuthor::<Runtime>::on_unbalanced(to_author);
+		}
+	}
+}
+
+/// Converts transaction fees from KMD into a foreign asset at the ratio of their prices of
+/// execution weight, rounded up so that no transaction is cheaper in a foreign asset. Only
+/// foreign assets priced in the asset registry can pay fees.
+pub struct RegistryRate;
+impl BalanceConversion<Balance, AssetId, Balance> for RegistryRate {
+	type Error = DispatchError;
+
+	fn to_asset_balance(balance: Balance, asset_id: AssetId) -> Result<Balance, DispatchError> {
+		let units_per_second =
+			AssetRegistry::units_per_second(asset_id).ok_or(TokenError::UnknownAsset)?;
+		let kmd_per_second = KmdPerSecond::get();
+		if kmd_per_second == 0 {
+			return Err(ArithmeticError::DivisionByZero.into())
+		}
+		// The product of two balances always fits in 256 bits.
+		let (fee, remainder) = (U256::from(balance) * U256::from(units_per_second))
+			.div_mod(U256::from(kmd_per_second));
+		let fee = fee + U256::from(u8::from(!remainder.is_zero()));
+		fee.try_into().map_err(|_| ArithmeticError::Overflow.into())
+	}
+}
+
+/// Deposits transaction fees paid in foreign assets into the treasury.
+///
+/// Deposits fail when the account cannot hold the asset, such as fees below the existential
+/// deposit of an asset the treasury holds none of. The fees then go to the block author, or else
+/// to the checking account, rather than being burned.
+pub struct AssetFeesToTreasury;
+impl HandleCredit<AccountId, Assets> for AssetFeesToTreasury {
+	fn handle_credit(mut credit: CreditOf<AccountId, Assets>) {
+		for who in [Treasury::account_id(), Authorship::author(), CheckingAccount::get()] {
+			let (asset_id, amount) = (credit.asset(), credit.peek());
+			match Assets::resolve(&who, credit) {
+				Ok(()) => return,
+				Err(unresolved) => {
+					log::warn!(
+						target: "runtime::fees",
+						"Failed to deposit fees of {:?} in asset {:?} into {:?}",
+						amount,
+						asset_id,
+						who,
+					);
+					credit = unresolved;
+				},
+			}
+		}
+		// Dropping the credit burns the fees nobody could hold.
+	}
+}
+
+/// The asset ids below `1 << 16` are kept for foreign assets.
+pub struct ForeignAssetIds;
+impl Contains<AssetId> for ForeignAssetIds {
+	fn contains(id: &AssetId) -> bool {
+		*id < 1 << 16
+	}
+}
+
+/// Filters the calls paused by `Maintenance`, the creation of assets with foreign asset ids,
+/// which would block registering the foreign assets, and setting the runtime code through the
+/// system pallet, which goes through `RuntimeUpgrade` instead. Like every base filter, it does
+/// not apply to calls dispatched as root.
+pub struct BaseFilter;
+impl Contains<Call> for BaseFilter {
+	fn contains(call: &Call) -> bool {
+		match call {
+			Call::Assets(pallet_assets::Call::create { id, .. })
+				if ForeignAssetIds::contains(id) =>
+				return false,
+			Call::System(
+				frame_system::Call::set_code { .. } |
+				frame_system::Call::set_code_without_checks { .. },
+			) => return false,
+			_ => (),
+		}
+		Maintenance::contains(call)
+	}
+}
+
+/// Treats an account as verified once a registrar judged its identity `Reasonable` or
+/// `KnownGood`.
+pub struct IdentityVerifier;
+impl VerifiedIdentities<AccountId> for IdentityVerifier {
+	fn is_verified(who: &AccountId) -> bool {
+		Identity::identity(who).map_or(false, |registration| {
+			registration.judgements.iter().any(|(_, judgement)| {
+				matches!(judgement, Judgement::Reasonable | Judgement::KnownGood)
+			})
+		})
+	}
+}
+
+/// The calls that stay available in maintenance mode: inherents, so that blocks keep being
+/// produced, and governance, so that maintenance mode can be left. Root calls, such as the ones
+/// scheduled by referenda, are never filtered.
+pub struct MaintenanceWhitelist;
+impl Contains<Call> for MaintenanceWhitelist {
+	fn contains(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::ParachainSystem(
+				forests_pallet_parachain_system::Call::set_validation_data { .. }
+			) | Call::Timestamp(pallet_timestamp::Call::set { .. }) |
+				Call::Preimage(
+					pallet_preimage::Call::note_preimage { .. } |
+						pallet_preimage::Call::unnote_preimage { .. }
+				) | Call::Council(..) |
+				Call::TechnicalCommittee(..) |
+				Call::ConvictionVoting(..) |
+				Call::Referenda(..) |
+				Call::RuntimeUpgrade(..)
+		)
+	}
+}
+
+/// Suspends the XCMP queue for maintenance mode.
+pub struct XcmExecutionManager;
+impl PauseXcmExecution for XcmExecutionManager {
+	fn suspend_xcm_execution() -> DispatchResult {
+		XcmpQueue::suspend_xcm_execution(Origin::root())
+	}
+
+	fn resume_xcm_execution() -> DispatchResult {
+		XcmpQueue::resume_xcm_execution(Origin::root())
+	}
+}
+
+/// Describes a registered foreign asset for wallets.
+pub fn foreign_asset(
+	asset_id: AssetId,
+	location: MultiLocation,
+	metadata: AssetMetadataOf<Runtime>,
+	units_per_second: Option<u128>,
+) -> ForeignAsset<AssetId, Balance> {
+	ForeignAsset {
+		asset_id,
+		location: VersionedMultiLocation::from(location),
+		name: metadata.name.into_inner(),
+		symbol: metadata.symbol.into_inner(),
+		decimals: metadata.decimals,
+		existential_deposit: metadata.existential_deposit,
+		units_per_second,
+	}
+}
@@ -7,17 +7,20 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 pub mod constants;
+pub mod governance;
+mod impls;
 mod weights;
 pub mod xcm_config;
 
+use codec::{Decode, Encode, MaxEncodedLen};
 use forests_pallet_parachain_system::RelayNumberStrictlyIncreases;
 use sp_api::impl_runtime_apis;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT},
+	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, ConvertInto},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult,
+	ApplyExtrinsicResult, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -26,16 +29,25 @@ use sp_version::RuntimeVersion;
 
 use frame_support::{
 	construct_runtime, parameter_types,
-	traits::Everything,
+	traits::{ConstU32, Everything, InstanceFilter},
 	weights::{constants::WEIGHT_PER_SECOND, ConstantMultiplier, DispatchClass, Weight},
 	PalletId,
 };
-use frame_system::{
-	limits::{BlockLength, BlockWeights},
-	EnsureRoot,
+use frame_system::limits::{BlockLength, BlockWeights};
+use governance::{
+	pallet_custom_origins, EnsureRootOrCollatorAdmin, EnsureRootOrHalfCouncil,
+	EnsureRootOrTreasurer, EnsureRootOrTwoThirdsTechnicalCommittee, EnsureRootOrXcmAdmin,
+};
+use impls::{
+	AssetFeesToTreasury, BaseFilter, DealWithFees, ForeignAssetIds, IdentityVerifier,
+	MaintenanceWhitelist, RegistryRate, XcmExecutionManager,
+};
+use pallet_asset_registry_runtime_api::ForeignAsset;
+pub use sp_runtime::{MultiAddress, Perbill, Percent, Permill};
+use xcm_config::{
+	Ancestry, CheckingAccount, LocationToAccountId, XcmConfig, XcmOriginToTransactDispatchOrigin,
+	XcmRouter,
 };
-pub use sp_runtime::{MultiAddress, Perbill, Permill};
-use xcm_config::{XcmConfig, XcmOriginToTransactDispatchOrigin};
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -44,12 +56,12 @@ pub use sp_runtime::BuildStorage;
 use selendra_runtime_common::{BlockHashCount, SlowAdjustingFeeUpdate};
 
 // XCM Imports
-use xcm::latest::prelude::BodyId;
+use xcm::{latest::prelude::BodyId, Version as XcmVersion, VersionedMultiLocation};
 use xcm_executor::XcmExecutor;
 
 pub use constants::{currency::*, fee::WeightToFee, time::*};
 use kumandra_primitive::{
-	opaque, AccountId, AuraId, Balance, BlockNumber, Hash, Header, Index, Signature,
+	opaque, AccountId, AssetId, AuraId, Balance, BlockNumber, Hash, Header, Index, Signature,
 };
 use weights::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight};
 
@@ -67,10 +79,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("kumandra"),
 	impl_name: create_runtime_str!("kuamndra"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -159,7 +171,7 @@ impl frame_system::Config for Runtime {
 	/// The weight of database operations that the runtime can invoke.
 	type DbWeight = RocksDbWeight;
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = Everything;
+	type BaseCallFilter = BaseFilter;
 	/// Weight information for the extrinsics of this pallet.
 	type SystemWeightInfo = ();
 	/// Block & extrinsics weights: base values and limits.
@@ -208,7 +220,7 @@ impl pallet_balances::Config for Runtime {
 	type Balance = Balance;
 	/// The ubiquitous event type.
 	type Event = Event;
-	type DustRemoval = ();
+	type DustRemoval = Treasury;
 	type ExistentialDeposit = ExistentialDeposit;
 	type AccountStore = System;
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
@@ -224,13 +236,159 @@ parameter_types! {
 
 impl pallet_transaction_payment::Config for Runtime {
 	type Event = Event;
-	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, DealWithFees>;
 	type WeightToFee = WeightToFee;
 	type LengthToFee = ConstantMultiplier<Balance, TransactionByteFee>;
 	type FeeMultiplierUpdate = SlowAdjustingFeeUpdate<Self>;
 	type OperationalFeeMultiplier = OperationalFeeMultiplier;
 }
 
+parameter_types! {
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = 100 * UNIT;
+	pub const SpendPeriod: BlockNumber = 6 * DAYS;
+	pub const Burn: Permill = Permill::zero();
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	pub const MaxApprovals: u32 = 100;
+}
+
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	// Proposals are approved and rejected by referenda on the treasurer track.
+	type ApproveOrigin = EnsureRootOrTreasurer;
+	type RejectOrigin = EnsureRootOrTreasurer;
+	type Event = Event;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type ProposalBondMaximum = ();
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+	type BurnDestination = ();
+	type SpendFunds = Bounties;
+	type WeightInfo = pallet_treasury::weights::SubstrateWeight<Runtime>;
+	type MaxApprovals = MaxApprovals;
+	type SpendOrigin = frame_support::traits::NeverEnsureOrigin<Balance>;
+}
+
+parameter_types! {
+	pub const BountyDepositBase: Balance = deposit(1, 0);
+	pub const BountyDepositPayoutDelay: BlockNumber = 4 * DAYS;
+	pub const BountyUpdatePeriod: BlockNumber = 90 * DAYS;
+	pub const CuratorDepositMultiplier: Permill = Permill::from_percent(50);
+	pub const CuratorDepositMin: Balance = 10 * UNIT;
+	pub const CuratorDepositMax: Balance = 200 * UNIT;
+	pub const BountyValueMinimum: Balance = 100 * UNIT;
+	pub const DataDepositPerByte: Balance = deposit(0, 1);
+	pub const MaximumReasonLength: u32 = 16384;
+}
+
+// Bounties are approved and their curators proposed by treasury's `ApproveOrigin`, i.e. referenda
+// on the treasurer track. Payouts come out of the treasury pot.
+impl pallet_bounties::Config for Runtime {
+	type Event = Event;
+	type BountyDepositBase = BountyDepositBase;
+	type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+	type BountyUpdatePeriod = BountyUpdatePeriod;
+	type CuratorDepositMultiplier = CuratorDepositMultiplier;
+	type CuratorDepositMin = CuratorDepositMin;
+	type CuratorDepositMax = CuratorDepositMax;
+	type BountyValueMinimum = BountyValueMinimum;
+	type DataDepositPerByte = DataDepositPerByte;
+	type MaximumReasonLength = MaximumReasonLength;
+	type WeightInfo = pallet_bounties::weights::SubstrateWeight<Runtime>;
+	type ChildBountyManager = ChildBounties;
+}
+
+parameter_types! {
+	pub const MaxActiveChildBountyCount: u32 = 100;
+	pub const ChildBountyValueMinimum: Balance = BountyValueMinimum::get() / 10;
+}
+
+impl pallet_child_bounties::Config for Runtime {
+	type Event = Event;
+	type MaxActiveChildBountyCount = MaxActiveChildBountyCount;
+	type ChildBountyValueMinimum = ChildBountyValueMinimum;
+	type WeightInfo = pallet_child_bounties::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const TipCountdown: BlockNumber = DAYS;
+	pub const TipFindersFee: Percent = Percent::from_percent(20);
+	pub const TipReportDepositBase: Balance = deposit(1, 0);
+}
+
+impl pallet_tips::Config for Runtime {
+	type Event = Event;
+	type DataDepositPerByte = DataDepositPerByte;
+	type MaximumReasonLength = MaximumReasonLength;
+	// The elected council members are the tippers.
+	type Tippers = Elections;
+	type TipCountdown = TipCountdown;
+	type TipFindersFee = TipFindersFee;
+	type TipReportDepositBase = TipReportDepositBase;
+	type WeightInfo = pallet_tips::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const MinVestedTransfer: Balance = 100 * MILLIUNIT;
+}
+
+impl pallet_vesting::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BlockNumberToBalance = ConvertInto;
+	type MinVestedTransfer = MinVestedTransfer;
+	type WeightInfo = pallet_vesting::weights::SubstrateWeight<Runtime>;
+	const MAX_VESTING_SCHEDULES: u32 = 28;
+}
+
+parameter_types! {
+	// One storage item; key size 16, value size of the asset details of 174 bytes.
+	pub const AssetDeposit: Balance = deposit(1, 190);
+	// One storage item; key size 48, value size 18 bytes.
+	pub const AssetAccountDeposit: Balance = deposit(1, 66);
+	pub const ApprovalDeposit: Balance = EXISTENTIAL_DEPOSIT;
+	pub const AssetsStringLimit: u32 = 50;
+	// One storage item; key size 16, value size 68 bytes plus name and symbol.
+	pub const MetadataDepositBase: Balance = deposit(1, 84);
+	pub const MetadataDepositPerByte: Balance = deposit(0, 1);
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = AssetsStringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_asset_registry::Config for Runtime {
+	type Event = Event;
+	type AssetId = AssetId;
+	type Assets = Assets;
+	type ForeignAssetOwner = CheckingAccount;
+	type ForeignAssetIds = ForeignAssetIds;
+	type RegistryOrigin = EnsureRootOrXcmAdmin;
+	type StringLimit = AssetsStringLimit;
+}
+
+impl pallet_asset_tx_payment::Config for Runtime {
+	type Fungibles = Assets;
+	type OnChargeAssetTransaction =
+		pallet_asset_tx_payment::FungiblesAdapter<RegistryRate, AssetFeesToTreasury>;
+}
+
 parameter_types! {
 	pub const ReservedXcmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
 	pub const ReservedDmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
@@ -241,7 +399,8 @@ impl forests_pallet_parachain_system::Config for Runtime {
 	type OnSystemEvent = ();
 	type SelfParaId = parachain_info::Pallet<Runtime>;
 	type OutboundXcmpMessageSource = XcmpQueue;
-	type DmpMessageHandler = DmpQueue;
+	// Keeps downward messages while XCM execution is suspended for maintenance.
+	type DmpMessageHandler = Maintenance;
 	type ReservedDmpWeight = ReservedDmpWeight;
 	type XcmpMessageHandler = XcmpQueue;
 	type ReservedXcmpWeight = ReservedXcmpWeight;
@@ -256,9 +415,9 @@ impl forests_pallet_xcmp_queue::Config for Runtime {
 	type Event = Event;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
 	type ChannelInfo = ParachainSystem;
-	type VersionWrapper = ();
-	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
-	type ControllerOrigin = EnsureRoot<AccountId>;
+	type VersionWrapper = SelendraXcm;
+	type ExecuteOverweightOrigin = EnsureRootOrXcmAdmin;
+	type ControllerOrigin = EnsureRootOrXcmAdmin;
 	type ControllerOriginConverter = XcmOriginToTransactDispatchOrigin;
 	type WeightInfo = ();
 }
@@ -266,7 +425,16 @@ impl forests_pallet_xcmp_queue::Config for Runtime {
 impl forests_pallet_dmp_queue::Config for Runtime {
 	type Event = Event;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
-	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type ExecuteOverweightOrigin = EnsureRootOrXcmAdmin;
+}
+
+impl pallet_maintenance::Config for Runtime {
+	type Event = Event;
+	type MaintenanceOrigin = EnsureRootOrTwoThirdsTechnicalCommittee;
+	type WhitelistedCalls = MaintenanceWhitelist;
+	type XcmExecutionManager = XcmExecutionManager;
+	type DmpMessageHandler = DmpQueue;
+	type MaxNameLength = ConstU32<64>;
 }
 
 parameter_types! {
@@ -304,8 +472,9 @@ parameter_types! {
 	pub const ExecutiveBody: BodyId = BodyId::Executive;
 }
 
-// We allow root only to execute privileged collator selection operations.
-pub type CollatorSelectionUpdateOrigin = EnsureRoot<AccountId>;
+// We allow root and referenda on the collator admin track to execute privileged collator selection
+// operations.
+pub type CollatorSelectionUpdateOrigin = EnsureRootOrCollatorAdmin;
 
 impl pallet_collator_selection::Config for Runtime {
 	type Event = Event;
@@ -328,6 +497,268 @@ impl pallet_template::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const PlotDeposit: Balance = deposit(1, 48);
+	// 1 GiB worth of 4 KiB pieces.
+	pub const MinPlotPieces: u64 = 1 << 18;
+	// 16 TiB worth of 4 KiB pieces.
+	pub const MaxPlotPieces: u64 = 1 << 32;
+	pub const ChallengePeriod: BlockNumber = HOURS;
+	// Has to stay below `BlockHashCount` so the challenge seed is still available.
+	pub const ProofWindow: BlockNumber = 10 * MINUTES;
+	pub const RewardPerPiece: Balance = MICROUNIT / 1_000;
+	pub const StoragePotId: PalletId = PalletId(*b"PotStore");
+	pub const AttestationValidity: BlockNumber = 7 * DAYS;
+}
+
+impl pallet_storage_provider::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type PlotDeposit = PlotDeposit;
+	type MinPlotPieces = MinPlotPieces;
+	type MaxPlotPieces = MaxPlotPieces;
+	type ChallengePeriod = ChallengePeriod;
+	type ProofWindow = ProofWindow;
+	type RewardPerPiece = RewardPerPiece;
+	type PotId = StoragePotId;
+	type AttestationValidity = AttestationValidity;
+	type ManagerOrigin = EnsureRootOrHalfCouncil;
+	type WeightInfo = pallet_storage_provider::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const MaxReplicas: u32 = 16;
+	pub const MaxDealCandidates: u32 = 64;
+	pub const MinDealDuration: BlockNumber = DAYS;
+	pub const MaxDealDuration: BlockNumber = 365 * DAYS;
+	pub const DeletionWindow: BlockNumber = 2 * DAYS;
+	pub const StorageDealsPalletId: PalletId = PalletId(*b"py/deals");
+}
+
+impl pallet_storage_deals::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type AssetId = AssetId;
+	type Assets = Assets;
+	type PalletId = StorageDealsPalletId;
+	type Providers = StorageProvider;
+	type MaxReplicas = MaxReplicas;
+	type MaxCandidates = MaxDealCandidates;
+	type MinDuration = MinDealDuration;
+	type MaxDuration = MaxDealDuration;
+	type DeletionWindow = DeletionWindow;
+	type Identities = IdentityVerifier;
+	// Siblings keep their XCM origin with a `Transact` of origin kind `Xcm`.
+	type XcmOrigin = pallet_xcm::EnsureXcm<Everything>;
+	type LocationToAccountId = LocationToAccountId;
+	type XcmRouter = XcmRouter;
+	type LocationInverter = xcm_builder::LocationInverter<Ancestry>;
+}
+
+impl pallet_utility::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type PalletsOrigin = OriginCaller;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
+	pub const DepositBase: Balance = deposit(1, 88);
+	// Additional storage item size of 32 bytes.
+	pub const DepositFactor: Balance = deposit(0, 32);
+	pub const MaxSignatories: u16 = 100;
+}
+
+impl pallet_multisig::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = DepositBase;
+	type DepositFactor = DepositFactor;
+	type MaxSignatories = MaxSignatories;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	// One storage item; key size 32, value size 8.
+	pub const ProxyDepositBase: Balance = deposit(1, 8);
+	// Additional storage item size of 33 bytes.
+	pub const ProxyDepositFactor: Balance = deposit(0, 33);
+	pub const MaxProxies: u16 = 32;
+	// One storage item; key size 32, value size 16.
+	pub const AnnouncementDepositBase: Balance = deposit(1, 16);
+	pub const AnnouncementDepositFactor: Balance = deposit(0, 68);
+	pub const MaxPending: u16 = 32;
+}
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(
+	Copy,
+	Clone,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// All calls.
+	Any,
+	/// Calls that neither transfer funds nor pay fees or bonds that a rejection forfeits.
+	NonTransfer,
+	/// Council, referenda and treasury calls.
+	Governance,
+	/// Collator candidacy and session key calls.
+	CollatorManagement,
+	/// Plot, proof and attestation calls of a storage provider, and confirming deletions.
+	StorageOperator,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			// An allow-list, so that calls added later cannot move funds until listed here.
+			ProxyType::NonTransfer => matches!(
+				c,
+				Call::System(..) |
+					Call::Vesting(pallet_vesting::Call::vest { .. }) |
+					Call::Vesting(pallet_vesting::Call::vest_other { .. }) |
+					Call::CollatorSelection(..) |
+					Call::Session(..) | Call::StorageProvider(..) |
+					Call::StorageDeals(pallet_storage_deals::Call::settle_deal { .. }) |
+					Call::StorageDeals(pallet_storage_deals::Call::request_deletion { .. }) |
+					Call::StorageDeals(pallet_storage_deals::Call::confirm_deletion { .. }) |
+					Call::StorageDeals(pallet_storage_deals::Call::close_deletion { .. }) |
+					Call::StorageDeals(pallet_storage_deals::Call::set_asking_price { .. }) |
+					Call::Council(..) | Call::TechnicalCommittee(..) |
+					Call::Elections(pallet_elections_phragmen::Call::vote { .. }) |
+					Call::Elections(pallet_elections_phragmen::Call::remove_voter { .. }) |
+					Call::Elections(pallet_elections_phragmen::Call::renounce_candidacy { .. }) |
+					Call::ConvictionVoting(..) |
+					Call::Referenda(..) | Call::Preimage(..) |
+					Call::Utility(..) | Call::Multisig(..) |
+					Call::Proxy(..) | Call::Identity(pallet_identity::Call::set_identity { .. }) |
+					Call::Identity(pallet_identity::Call::set_subs { .. }) |
+					Call::Identity(pallet_identity::Call::clear_identity { .. }) |
+					Call::Identity(pallet_identity::Call::cancel_request { .. }) |
+					Call::Identity(pallet_identity::Call::add_sub { .. }) |
+					Call::Identity(pallet_identity::Call::rename_sub { .. }) |
+					Call::Identity(pallet_identity::Call::remove_sub { .. }) |
+					Call::Identity(pallet_identity::Call::quit_sub { .. }) |
+					Call::Recovery(pallet_recovery::Call::vouch_recovery { .. }) |
+					Call::Recovery(pallet_recovery::Call::claim_recovery { .. }) |
+					Call::Recovery(pallet_recovery::Call::close_recovery { .. }) |
+					Call::Recovery(pallet_recovery::Call::remove_recovery { .. }) |
+					Call::Recovery(pallet_recovery::Call::cancel_recovered { .. })
+			),
+			ProxyType::Governance => matches!(
+				c,
+				Call::Council(..) |
+					Call::TechnicalCommittee(..) |
+					Call::Elections(..) | Call::ConvictionVoting(..) |
+					Call::Referenda(..) | Call::Preimage(..) |
+					Call::Treasury(..) | Call::Bounties(..) |
+					Call::ChildBounties(..) |
+					Call::Tips(..) | Call::Utility(..)
+			),
+			ProxyType::CollatorManagement =>
+				matches!(c, Call::CollatorSelection(..) | Call::Session(..) | Call::Utility(..)),
+			ProxyType::StorageOperator => matches!(
+				c,
+				Call::StorageProvider(..) |
+					Call::StorageDeals(pallet_storage_deals::Call::confirm_deletion { .. }) |
+					Call::Utility(..)
+			),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::NonTransfer, ProxyType::CollatorManagement) => true,
+			(ProxyType::NonTransfer, ProxyType::StorageOperator) => true,
+			_ => false,
+		}
+	}
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = MaxPending;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+parameter_types! {
+	// One storage item; key size 32, value size of a minimal identity of 258 bytes.
+	pub const BasicDeposit: Balance = deposit(1, 258);
+	// Additional field of 66 bytes.
+	pub const FieldDeposit: Balance = deposit(0, 66);
+	// One storage item; key size 32, value size 21 bytes.
+	pub const SubAccountDeposit: Balance = deposit(1, 53);
+	pub const MaxSubAccounts: u32 = 100;
+	pub const MaxAdditionalFields: u32 = 100;
+	pub const MaxRegistrars: u32 = 20;
+}
+
+impl pallet_identity::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BasicDeposit = BasicDeposit;
+	type FieldDeposit = FieldDeposit;
+	type SubAccountDeposit = SubAccountDeposit;
+	type MaxSubAccounts = MaxSubAccounts;
+	type MaxAdditionalFields = MaxAdditionalFields;
+	type MaxRegistrars = MaxRegistrars;
+	type Slashed = Treasury;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type RegistrarOrigin = EnsureRootOrHalfCouncil;
+	type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	// One storage item; key size 32, value size 4+16+1 bytes plus the friends.
+	pub const ConfigDepositBase: Balance = deposit(1, 53);
+	// Additional friend of 32 bytes.
+	pub const FriendDepositFactor: Balance = deposit(0, 32);
+	pub const MaxFriends: u16 = 9;
+	// One storage item; key size 64, value size 4+16 bytes plus the vouching friends.
+	pub const RecoveryDeposit: Balance = deposit(1, 84);
+}
+
+impl pallet_recovery::Config for Runtime {
+	type Event = Event;
+	type WeightInfo = pallet_recovery::weights::SubstrateWeight<Runtime>;
+	type Call = Call;
+	type Currency = Balances;
+	type ConfigDepositBase = ConfigDepositBase;
+	type FriendDepositFactor = FriendDepositFactor;
+	type MaxFriends = MaxFriends;
+	type RecoveryDeposit = RecoveryDeposit;
+}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub enum Runtime where
@@ -342,10 +773,19 @@ construct_runtime!(
 		} = 1,
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent} = 2,
 		ParachainInfo: parachain_info::{Pallet, Storage, Config} = 3,
+		Maintenance: pallet_maintenance::{Pallet, Call, Storage, Event<T>} = 4,
 
 		// Monetary stuff.
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 10,
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage, Event<T>} = 11,
+		Treasury: pallet_treasury::{Pallet, Call, Storage, Config, Event<T>} = 12,
+		Bounties: pallet_bounties::{Pallet, Call, Storage, Event<T>} = 13,
+		ChildBounties: pallet_child_bounties::{Pallet, Call, Storage, Event<T>} = 14,
+		Tips: pallet_tips::{Pallet, Call, Storage, Event<T>} = 15,
+		Vesting: pallet_vesting::{Pallet, Call, Storage, Event<T>, Config<T>} = 16,
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>, Config<T>} = 17,
+		AssetRegistry: pallet_asset_registry::{Pallet, Call, Storage, Event<T>, Config<T>} = 18,
+		AssetTxPayment: pallet_asset_tx_payment::{Pallet} = 19,
 
 		// Collator support. The order of these 4 are important and shall not change.
 		Authorship: pallet_authorship::{Pallet, Call, Storage} = 20,
@@ -359,9 +799,33 @@ construct_runtime!(
 		SelendraXcm: pallet_xcm::{Pallet, Call, Event<T>, Origin, Config} = 31,
 		ForestsXcm: forests_pallet_xcm::{Pallet, Event<T>, Origin} = 32,
 		DmpQueue: forests_pallet_dmp_queue::{Pallet, Call, Storage, Event<T>} = 33,
+		XcmTransfer: pallet_xcm_transfer::{Pallet, Call, Storage, Event<T>} = 34,
+		XcmVersions: pallet_xcm_versions::{Pallet, Call} = 35,
 
 		// Template
 		TemplatePallet: pallet_template::{Pallet, Call, Storage, Event<T>}  = 40,
+
+		// Storage.
+		StorageProvider: pallet_storage_provider::{Pallet, Call, Storage, Event<T>} = 50,
+		StorageDeals: pallet_storage_deals::{Pallet, Call, Storage, Event<T>} = 51,
+
+		// Governance.
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>} = 60,
+		Preimage: pallet_preimage::{Pallet, Call, Storage, Event<T>} = 61,
+		Council: pallet_collective::<Instance1>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 62,
+		TechnicalCommittee: pallet_collective::<Instance2>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 63,
+		Elections: pallet_elections_phragmen::{Pallet, Call, Storage, Event<T>, Config<T>} = 64,
+		ConvictionVoting: pallet_conviction_voting::{Pallet, Call, Storage, Event<T>} = 65,
+		Referenda: pallet_referenda::{Pallet, Call, Storage, Event<T>} = 66,
+		Origins: pallet_custom_origins::{Origin} = 67,
+		RuntimeUpgrade: pallet_runtime_upgrade::{Pallet, Call, Storage, Event<T>} = 68,
+
+		// Account helpers.
+		Utility: pallet_utility::{Pallet, Call, Event} = 70,
+		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>} = 71,
+		Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>} = 72,
+		Identity: pallet_identity::{Pallet, Call, Storage, Event<T>} = 73,
+		Recovery: pallet_recovery::{Pallet, Call, Storage, Event<T>} = 74,
 	}
 );
 
@@ -383,7 +847,8 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	// Fees are paid in KMD, or in a foreign asset priced in the asset registry.
+	pallet_asset_tx_payment::ChargeAssetTxPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -410,10 +875,14 @@ mod benches {
 	define_benchmarks!(
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_assets, Assets]
 		[pallet_session, SessionBench::<Runtime>]
 		[pallet_timestamp, Timestamp]
 		[pallet_collator_selection, CollatorSelection]
 		[forests_pallet_xcmp_queue, XcmpQueue]
+		[pallet_storage_provider, StorageProvider]
+		[pallet_xcm_benchmarks::fungible, XcmBalances]
+		[pallet_xcm_benchmarks::generic, XcmGeneric]
 	);
 }
 
@@ -518,6 +987,59 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_storage_deals_runtime_api::StorageStatsApi<Block, Balance> for Runtime {
+		fn storage_stats() -> pallet_storage_deals_runtime_api::StorageStats<Balance> {
+			let (active_deals, average_price) = StorageDeals::active_deals();
+			pallet_storage_deals_runtime_api::StorageStats {
+				committed_capacity: StorageProvider::committed_capacity(),
+				used_capacity: StorageDeals::total_used_capacity(),
+				active_deals,
+				average_price,
+				proof_success_rate: StorageProvider::proof_success_rate(),
+			}
+		}
+	}
+
+	impl pallet_storage_deals_runtime_api::StorageDealsApi<Block, AccountId> for Runtime {
+		fn eligible_providers(size: u64, verified_only: bool) -> Vec<AccountId> {
+			pallet_storage_provider::Plots::<Runtime>::iter_keys()
+				.filter(|who| StorageDeals::is_eligible(who, size, verified_only))
+				.collect()
+		}
+
+		fn is_servable(deal_id: u64) -> bool {
+			StorageDeals::is_servable(deal_id)
+		}
+	}
+
+	impl pallet_asset_registry_runtime_api::AssetRegistryApi<Block, AssetId, Balance> for Runtime {
+		fn foreign_assets() -> Vec<ForeignAsset<AssetId, Balance>> {
+			AssetRegistry::foreign_assets()
+				.into_iter()
+				.map(|(asset_id, location, metadata, units_per_second)| {
+					impls::foreign_asset(asset_id, location, metadata, units_per_second)
+				})
+				.collect()
+		}
+
+		fn foreign_asset(asset_id: AssetId) -> Option<ForeignAsset<AssetId, Balance>> {
+			let location = AssetRegistry::asset_location(asset_id)?;
+			let metadata = AssetRegistry::asset_metadata(asset_id)?;
+			let units_per_second = AssetRegistry::units_per_second(asset_id);
+			Some(impls::foreign_asset(asset_id, location, metadata, units_per_second))
+		}
+	}
+
+	impl pallet_xcm_versions_runtime_api::XcmVersionsApi<Block> for Runtime {
+		fn version_for(dest: VersionedMultiLocation) -> Option<XcmVersion> {
+			XcmVersions::version_for(&dest.try_into().ok()?)
+		}
+
+		fn current_version() -> XcmVersion {
+			xcm::latest::VERSION
+		}
+	}
+
 	impl forests_primitives_core::CollectCollationInfo<Block> for Runtime {
 		fn collect_collation_info(header: &<Block as BlockT>::Header) -> forests_primitives_core::CollationInfo {
 			ParachainSystem::collect_collation_info(header)
@@ -547,6 +1069,8 @@ impl_runtime_apis! {
 			use frame_support::traits::StorageInfoTrait;
 			use frame_system_benchmarking::Pallet as SystemBench;
 			use forests_pallet_session_benchmarking::Pallet as SessionBench;
+			type XcmBalances = pallet_xcm_benchmarks::fungible::Pallet::<Runtime>;
+			type XcmGeneric = pallet_xcm_benchmarks::generic::Pallet::<Runtime>;
 
 			let mut list = Vec::<BenchmarkList>::new();
 			list_benchmarks!(list, extra);
@@ -566,6 +1090,57 @@ impl_runtime_apis! {
 			use forests_pallet_session_benchmarking::Pallet as SessionBench;
 			impl forests_pallet_session_benchmarking::Config for Runtime {}
 
+			use frame_benchmarking::BenchmarkError;
+			use xcm::latest::prelude::*;
+			use xcm_config::{LocationToAccountId, RelayLocation, SelfReserve};
+
+			impl pallet_xcm_benchmarks::Config for Runtime {
+				type XcmConfig = XcmConfig;
+				type AccountIdConverter = LocationToAccountId;
+				fn valid_destination() -> Result<MultiLocation, BenchmarkError> {
+					// Siblings need an open channel, the relay chain is always reachable.
+					Ok(RelayLocation::get())
+				}
+				fn worst_case_holding() -> MultiAssets {
+					// KMD is the only asset benchmarked.
+					vec![MultiAsset {
+						id: Concrete(SelfReserve::get()),
+						fun: Fungible(1_000_000 * UNIT),
+					}].into()
+				}
+			}
+
+			impl pallet_xcm_benchmarks::fungible::Config for Runtime {
+				type TransactAsset = Balances;
+				// Teleporting is disabled.
+				type CheckedAccount = ();
+				type TrustedTeleporter = ();
+				fn get_multi_asset() -> MultiAsset {
+					MultiAsset { id: Concrete(SelfReserve::get()), fun: Fungible(UNIT) }
+				}
+			}
+
+			impl pallet_xcm_benchmarks::generic::Config for Runtime {
+				type Call = Call;
+				fn worst_case_response() -> (u64, Response) {
+					(0u64, Response::Version(Default::default()))
+				}
+				fn transact_origin() -> Result<MultiLocation, BenchmarkError> {
+					Ok(RelayLocation::get())
+				}
+				fn subscribe_origin() -> Result<MultiLocation, BenchmarkError> {
+					Ok(RelayLocation::get())
+				}
+				fn claimable_asset(
+				) -> Result<(MultiLocation, MultiLocation, MultiAssets), BenchmarkError> {
+					let assets: MultiAssets = (Concrete(SelfReserve::get()), 1_000 * UNIT).into();
+					Ok((RelayLocation::get(), MultiLocation::here(), assets))
+				}
+			}
+
+			type XcmBalances = pallet_xcm_benchmarks::fungible::Pallet::<Runtime>;
+			type XcmGeneric = pallet_xcm_benchmarks::generic::Pallet::<Runtime>;
+
 			let whitelist: Vec<TrackedStorageKey> = vec![
 				// Block Number
 				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
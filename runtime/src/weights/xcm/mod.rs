@@ -0,0 +1,287 @@
+//! Weights of XCM instructions, from the benchmarks of `pallet_xcm_benchmarks` for KMD and of
+//! `pallet_assets` for the other assets.
+
+mod pallet_xcm_benchmarks_fungible;
+mod pallet_xcm_benchmarks_generic;
+
+use crate::{xcm_config::MaxAssetsIntoHolding, Runtime};
+use frame_support::{traits::Get, weights::Weight};
+use pallet_assets::WeightInfo as _;
+use sp_std::marker::PhantomData;
+use xcm::{latest::prelude::*, DoubleEncoded};
+
+use pallet_xcm_benchmarks_fungible::WeightInfo as XcmBalancesWeight;
+use pallet_xcm_benchmarks_generic::WeightInfo as XcmGeneric;
+
+type AssetsWeight = <Runtime as pallet_assets::Config>::WeightInfo;
+
+/// The kinds of assets the runtime transacts.
+enum AssetTypes {
+	/// KMD, in `pallet-balances`.
+	Balances,
+	/// An asset in `pallet-assets`, either foreign or issued on this chain.
+	Assets,
+	/// An asset the runtime cannot transact.
+	Unknown,
+}
+
+impl From<&MultiAsset> for AssetTypes {
+	fn from(asset: &MultiAsset) -> Self {
+		match asset.id {
+			Concrete(MultiLocation { parents: 0, interior: Here }) => AssetTypes::Balances,
+			Concrete(_) => AssetTypes::Assets,
+			Abstract(_) => AssetTypes::Unknown,
+		}
+	}
+}
+
+impl AssetTypes {
+	/// The weight of an instruction moving this asset, given its weights for KMD and for an
+	/// asset in `pallet-assets`.
+	fn weight(self, balances_weight: Weight, assets_weight: Weight) -> Weight {
+		match self {
+			AssetTypes::Balances => balances_weight,
+			AssetTypes::Assets => assets_weight,
+			AssetTypes::Unknown => Weight::MAX,
+		}
+	}
+}
+
+/// The weight of moving an asset in `pallet-assets`, from the benchmark of the extrinsic moving
+/// it the same way. Like the pallet itself, it uses the reference weights of `pallet-assets`
+/// until the benchmark is run for this runtime.
+///
+/// The asset transactor first looks the location of the asset up in the registry, which the
+/// benchmarks of `pallet-assets` do not cover.
+fn assets_weight(extrinsic_weight: Weight) -> Weight {
+	extrinsic_weight.saturating_add(<Runtime as frame_system::Config>::DbWeight::get().reads(1))
+}
+
+trait WeighMultiAssets {
+	/// The weight of an instruction moving the assets, given its weights for moving KMD and an
+	/// asset in `pallet-assets`.
+	fn weigh_multi_assets(&self, balances_weight: Weight, assets_weight: Weight) -> Weight;
+}
+
+impl WeighMultiAssets for MultiAssets {
+	fn weigh_multi_assets(&self, balances_weight: Weight, assets_weight: Weight) -> Weight {
+		self.inner()
+			.iter()
+			.map(|asset| AssetTypes::from(asset).weight(balances_weight, assets_weight))
+			.fold(0, |acc, weight| acc.saturating_add(weight))
+	}
+}
+
+impl WeighMultiAssets for MultiAssetFilter {
+	fn weigh_multi_assets(&self, balances_weight: Weight, assets_weight: Weight) -> Weight {
+		self.weigh_up_to(MaxAssetsIntoHolding::get(), balances_weight, assets_weight)
+	}
+}
+
+trait WeighLimitedMultiAssets {
+	/// The weight of an instruction moving at most `max_assets` of the assets matching the
+	/// filter.
+	///
+	/// Wildcards match at most the assets in holding, which the barrier keeps within
+	/// `MaxAssetsIntoHolding`.
+	fn weigh_up_to(
+		&self,
+		max_assets: u32,
+		balances_weight: Weight,
+		assets_weight: Weight,
+	) -> Weight;
+}
+
+impl WeighLimitedMultiAssets for MultiAssetFilter {
+	fn weigh_up_to(
+		&self,
+		max_assets: u32,
+		balances_weight: Weight,
+		assets_weight: Weight,
+	) -> Weight {
+		match self {
+			Self::Definite(assets) => assets.weigh_multi_assets(balances_weight, assets_weight),
+			Self::Wild(_) => {
+				let max_assets = max_assets.min(MaxAssetsIntoHolding::get());
+				balances_weight.max(assets_weight).saturating_mul(max_assets as Weight)
+			},
+		}
+	}
+}
+
+/// The weight of `TransferReserveAsset` without the transfers of the assets, which is the weight
+/// of sending the message to the destination.
+fn transfer_reserve_asset_send() -> Weight {
+	XcmBalancesWeight::<Runtime>::transfer_reserve_asset()
+		.saturating_sub(XcmBalancesWeight::<Runtime>::transfer_asset())
+}
+
+/// The weight of `DepositReserveAsset` without the deposits of the assets, which is the weight of
+/// sending the message to the destination.
+fn deposit_reserve_asset_send() -> Weight {
+	XcmBalancesWeight::<Runtime>::deposit_reserve_asset()
+		.saturating_sub(XcmBalancesWeight::<Runtime>::deposit_asset())
+}
+
+/// Weighs every XCM instruction from its benchmark.
+pub struct KumandraXcmWeight<Call>(PhantomData<Call>);
+impl<Call> XcmWeightInfo<Call> for KumandraXcmWeight<Call> {
+	fn withdraw_asset(assets: &MultiAssets) -> Weight {
+		assets.weigh_multi_assets(
+			XcmBalancesWeight::<Runtime>::withdraw_asset(),
+			assets_weight(AssetsWeight::burn()),
+		)
+	}
+	fn reserve_asset_deposited(assets: &MultiAssets) -> Weight {
+		// The benchmark is overridden with a whole block. The instruction only checks the reserve
+		// and puts the assets into holding, without touching storage, like `ClearOrigin`.
+		assets.weigh_multi_assets(
+			XcmGeneric::<Runtime>::clear_origin(),
+			XcmGeneric::<Runtime>::clear_origin(),
+		)
+	}
+	fn receive_teleported_asset(_assets: &MultiAssets) -> Weight {
+		// Teleporting is disabled.
+		Weight::MAX
+	}
+	fn query_response(_query_id: &u64, _response: &Response, _max_weight: &u64) -> Weight {
+		XcmGeneric::<Runtime>::query_response()
+	}
+	fn transfer_asset(assets: &MultiAssets, _beneficiary: &MultiLocation) -> Weight {
+		assets.weigh_multi_assets(
+			XcmBalancesWeight::<Runtime>::transfer_asset(),
+			assets_weight(AssetsWeight::transfer()),
+		)
+	}
+	fn transfer_reserve_asset(
+		assets: &MultiAssets,
+		_dest: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> Weight {
+		// The message is sent once, whatever the number of assets.
+		transfer_reserve_asset_send().saturating_add(assets.weigh_multi_assets(
+			XcmBalancesWeight::<Runtime>::transfer_asset(),
+			assets_weight(AssetsWeight::transfer()),
+		))
+	}
+	fn transact(
+		_origin_type: &OriginKind,
+		_require_weight_at_most: &u64,
+		_call: &DoubleEncoded<Call>,
+	) -> Weight {
+		// The weight of the call itself is added by the weigher.
+		XcmGeneric::<Runtime>::transact()
+	}
+	fn hrmp_new_channel_open_request(
+		_sender: &u32,
+		_max_message_size: &u32,
+		_max_capacity: &u32,
+	) -> Weight {
+		// The executor does not support HRMP channel operations.
+		Weight::MAX
+	}
+	fn hrmp_channel_accepted(_recipient: &u32) -> Weight {
+		// The executor does not support HRMP channel operations.
+		Weight::MAX
+	}
+	fn hrmp_channel_closing(_initiator: &u32, _sender: &u32, _recipient: &u32) -> Weight {
+		// The executor does not support HRMP channel operations.
+		Weight::MAX
+	}
+	fn clear_origin() -> Weight {
+		XcmGeneric::<Runtime>::clear_origin()
+	}
+	fn descend_origin(_who: &InteriorMultiLocation) -> Weight {
+		XcmGeneric::<Runtime>::descend_origin()
+	}
+	fn report_error(
+		_query_id: &QueryId,
+		_dest: &MultiLocation,
+		_max_response_weight: &u64,
+	) -> Weight {
+		XcmGeneric::<Runtime>::report_error()
+	}
+	fn deposit_asset(
+		assets: &MultiAssetFilter,
+		max_assets: &u32,
+		_beneficiary: &MultiLocation,
+	) -> Weight {
+		assets.weigh_up_to(
+			*max_assets,
+			XcmBalancesWeight::<Runtime>::deposit_asset(),
+			assets_weight(AssetsWeight::mint()),
+		)
+	}
+	fn deposit_reserve_asset(
+		assets: &MultiAssetFilter,
+		max_assets: &u32,
+		_dest: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> Weight {
+		deposit_reserve_asset_send().saturating_add(assets.weigh_up_to(
+			*max_assets,
+			XcmBalancesWeight::<Runtime>::deposit_asset(),
+			assets_weight(AssetsWeight::mint()),
+		))
+	}
+	fn exchange_asset(_give: &MultiAssetFilter, _receive: &MultiAssets) -> Weight {
+		// The executor does not support exchanging assets.
+		Weight::MAX
+	}
+	fn initiate_reserve_withdraw(
+		assets: &MultiAssetFilter,
+		_reserve: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> Weight {
+		// The assets are only taken out of holding, and the message is sent once.
+		XcmGeneric::<Runtime>::initiate_reserve_withdraw().saturating_add(
+			assets.weigh_multi_assets(
+				XcmGeneric::<Runtime>::clear_origin(),
+				XcmGeneric::<Runtime>::clear_origin(),
+			),
+		)
+	}
+	fn initiate_teleport(
+		_assets: &MultiAssetFilter,
+		_dest: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> Weight {
+		// Teleporting is disabled.
+		Weight::MAX
+	}
+	fn query_holding(
+		_query_id: &u64,
+		_dest: &MultiLocation,
+		_assets: &MultiAssetFilter,
+		_max_response_weight: &u64,
+	) -> Weight {
+		XcmGeneric::<Runtime>::query_holding()
+	}
+	fn buy_execution(_fees: &MultiAsset, _weight_limit: &WeightLimit) -> Weight {
+		XcmGeneric::<Runtime>::buy_execution()
+	}
+	fn refund_surplus() -> Weight {
+		XcmGeneric::<Runtime>::refund_surplus()
+	}
+	fn set_error_handler(_xcm: &Xcm<Call>) -> Weight {
+		XcmGeneric::<Runtime>::set_error_handler()
+	}
+	fn set_appendix(_xcm: &Xcm<Call>) -> Weight {
+		XcmGeneric::<Runtime>::set_appendix()
+	}
+	fn clear_error() -> Weight {
+		XcmGeneric::<Runtime>::clear_error()
+	}
+	fn claim_asset(_assets: &MultiAssets, _ticket: &MultiLocation) -> Weight {
+		XcmGeneric::<Runtime>::claim_asset()
+	}
+	fn trap(_code: &u64) -> Weight {
+		XcmGeneric::<Runtime>::trap()
+	}
+	fn subscribe_version(_query_id: &QueryId, _max_response_weight: &u64) -> Weight {
+		XcmGeneric::<Runtime>::subscribe_version()
+	}
+	fn unsubscribe_version() -> Weight {
+		XcmGeneric::<Runtime>::unsubscribe_version()
+	}
+}
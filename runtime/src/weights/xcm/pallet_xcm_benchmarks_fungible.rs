@@ -0,0 +1,66 @@
+//! Weights for `pallet_xcm_benchmarks::fungible`, the XCM instructions moving KMD.
+//!
+//! These weights have not been measured for this runtime. They were taken from the benchmarks
+//! of the same instructions in other runtimes, with the storage accesses of this runtime, and
+//! must be regenerated on reference hardware before they can be relied on:
+//!
+//! ./target/release/kumandra-node benchmark pallet --chain=dev --steps=50 --repeat=20
+//! --pallet=pallet_xcm_benchmarks::fungible --extrinsic='*' --execution=wasm
+//! --wasm-execution=compiled --heap-pages=4096
+//! --template=../selendra/xcm/pallet-xcm-benchmarks/template.hbs
+//! --output=./runtime/src/weights/xcm/pallet_xcm_benchmarks_fungible.rs
+//!
+//! The teleport benchmarks are skipped, as teleporting is disabled, and the benchmark of
+//! `ReserveAssetDeposited` is overridden with a whole block.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weights for `pallet_xcm_benchmarks::fungible`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo<T> {
+	// Storage: System Account (r:1 w:1)
+	pub(crate) fn withdraw_asset() -> Weight {
+		(20_385_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:2 w:2)
+	pub(crate) fn transfer_asset() -> Weight {
+		(32_756_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: System Account (r:2 w:2)
+	// Storage: SelendraXcm SupportedVersion (r:1 w:0)
+	// Storage: SelendraXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: SelendraXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	pub(crate) fn transfer_reserve_asset() -> Weight {
+		(50_645_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: System Account (r:1 w:1)
+	pub(crate) fn deposit_asset() -> Weight {
+		(21_763_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:1)
+	// Storage: SelendraXcm SupportedVersion (r:1 w:0)
+	// Storage: SelendraXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: SelendraXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	pub(crate) fn deposit_reserve_asset() -> Weight {
+		(40_930_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
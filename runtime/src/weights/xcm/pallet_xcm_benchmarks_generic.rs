@@ -0,0 +1,108 @@
+//! Weights for `pallet_xcm_benchmarks::generic`, the XCM instructions not moving assets.
+//!
+//! These weights have not been measured for this runtime. They were taken from the benchmarks
+//! of the same instructions in other runtimes, with the storage accesses of this runtime, and
+//! must be regenerated on reference hardware before they can be relied on:
+//!
+//! ./target/release/kumandra-node benchmark pallet --chain=dev --steps=50 --repeat=20
+//! --pallet=pallet_xcm_benchmarks::generic --extrinsic='*' --execution=wasm
+//! --wasm-execution=compiled --heap-pages=4096
+//! --template=../selendra/xcm/pallet-xcm-benchmarks/template.hbs
+//! --output=./runtime/src/weights/xcm/pallet_xcm_benchmarks_generic.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weights for `pallet_xcm_benchmarks::generic`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo<T> {
+	// Storage: SelendraXcm SupportedVersion (r:1 w:0)
+	// Storage: SelendraXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: SelendraXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	pub(crate) fn query_holding() -> Weight {
+		(21_822_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	pub(crate) fn buy_execution() -> Weight {
+		(3_109_000 as Weight)
+	}
+	// Storage: SelendraXcm Queries (r:1 w:1)
+	pub(crate) fn query_response() -> Weight {
+		(12_087_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	pub(crate) fn transact() -> Weight {
+		(12_398_000 as Weight)
+	}
+	pub(crate) fn refund_surplus() -> Weight {
+		(3_247_000 as Weight)
+	}
+	pub(crate) fn set_error_handler() -> Weight {
+		(3_086_000 as Weight)
+	}
+	pub(crate) fn set_appendix() -> Weight {
+		(3_112_000 as Weight)
+	}
+	pub(crate) fn clear_error() -> Weight {
+		(3_111_000 as Weight)
+	}
+	pub(crate) fn descend_origin() -> Weight {
+		(4_052_000 as Weight)
+	}
+	pub(crate) fn clear_origin() -> Weight {
+		(3_103_000 as Weight)
+	}
+	// Storage: SelendraXcm SupportedVersion (r:1 w:0)
+	// Storage: SelendraXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: SelendraXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	pub(crate) fn report_error() -> Weight {
+		(13_664_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: SelendraXcm AssetTraps (r:1 w:1)
+	pub(crate) fn claim_asset() -> Weight {
+		(7_514_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	pub(crate) fn trap() -> Weight {
+		(3_065_000 as Weight)
+	}
+	// Storage: SelendraXcm VersionNotifyTargets (r:1 w:1)
+	// Storage: SelendraXcm SupportedVersion (r:1 w:0)
+	// Storage: SelendraXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: SelendraXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	pub(crate) fn subscribe_version() -> Weight {
+		(14_904_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: SelendraXcm VersionNotifyTargets (r:0 w:1)
+	pub(crate) fn unsubscribe_version() -> Weight {
+		(5_165_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: SelendraXcm SupportedVersion (r:1 w:0)
+	// Storage: SelendraXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: SelendraXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	pub(crate) fn initiate_reserve_withdraw() -> Weight {
+		(16_021_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+}
@@ -1,31 +1,49 @@
 use super::{
-	AccountId, Balances, Call, Event, Origin, ParachainInfo, ParachainSystem, Runtime, SelendraXcm,
-	WeightToFee, XcmpQueue,
+	governance::EnsureRootOrXcmAdmin, weights::xcm::KumandraXcmWeight, AccountId, AssetId,
+	AssetRegistry, Assets, Authorship, Balance, Balances, Call, Event, Origin, ParachainInfo,
+	ParachainSystem, Runtime, SelendraXcm, Treasury, WeightToFee, XcmTransfer, XcmpQueue,
 };
 use core::marker::PhantomData;
 use frame_support::{
-	log, match_types, parameter_types,
-	traits::{Everything, Nothing},
-	weights::Weight,
+	ensure, log, match_types, parameter_types,
+	traits::{Contains, Everything, Get, Nothing, PalletInfoAccess},
+	weights::{constants::WEIGHT_PER_SECOND, Weight, WeightToFee as _},
 };
+use pallet_asset_registry::{ExceptForeignAssets, UsingRegisteredAssets};
 use pallet_xcm::XcmPassthrough;
 use selendra_parachain::primitives::Sibling;
 use selendra_runtime_common::impls::ToAuthor;
+use sp_runtime::traits::Convert;
 use xcm::latest::prelude::*;
 use xcm_builder::{
-	AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, CurrencyAdapter,
-	EnsureXcmOrigin, FixedWeightBounds, IsConcrete, LocationInverter, NativeAsset, ParentIsPreset,
-	RelayChainAsNative, SiblingParachainAsNative, SiblingParachainConvertsVia,
-	SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation, TakeWeightCredit,
-	UsingComponents,
+	Account32Hash, AccountId32Aliases, AllowKnownQueryResponses, AllowSubscriptionsFrom,
+	AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, AsPrefixedGeneralIndex,
+	ConvertedConcreteAssetId, CurrencyAdapter, EnsureXcmOrigin, FungiblesAdapter, IsConcrete,
+	LocationInverter, ParentIsPreset, RelayChainAsNative, SiblingParachainAsNative,
+	SiblingParachainConvertsVia, SignedAccountId32AsNative, SignedToAccountId32,
+	SovereignSignedViaLocation, TakeWeightCredit, UsingComponents, WeightInfoBounds,
+};
+use xcm_executor::{
+	traits::{FilterAssetLocation, JustTry, ShouldExecute, TakeRevenue, TransactAsset},
+	XcmExecutor,
 };
-use xcm_executor::{traits::ShouldExecute, XcmExecutor};
 
 parameter_types! {
 	pub const RelayLocation: MultiLocation = MultiLocation::parent();
 	pub const RelayNetwork: NetworkId = NetworkId::Any;
 	pub RelayChainOrigin: Origin = forests_pallet_xcm::Origin::Relay.into();
 	pub Ancestry: MultiLocation = Parachain(ParachainInfo::parachain_id().into()).into();
+	/// The location of KMD, of which this chain is the reserve.
+	pub const SelfReserve: MultiLocation = MultiLocation::here();
+	/// The asset holding the relay-chain token on this chain.
+	pub const RelayAssetId: AssetId = 0;
+	/// The location of the assets pallet, under which locally issued assets are identified by
+	/// their asset id.
+	pub AssetsPalletLocation: MultiLocation =
+		PalletInstance(<Assets as PalletInfoAccess>::index() as u8).into();
+	/// The account of `pallet_xcm` that owns the foreign assets and takes the fees nobody else
+	/// can hold. It keeps track of no teleports, which are disabled.
+	pub CheckingAccount: AccountId = SelendraXcm::check_account();
 }
 
 /// Type for specifying how a `MultiLocation` can be converted into an `AccountId`. This is used
@@ -38,22 +56,55 @@ pub type LocationToAccountId = (
 	SiblingParachainConvertsVia<Sibling, AccountId>,
 	// Straight up local `AccountId32` origins just alias directly to `AccountId`.
 	AccountId32Aliases<RelayNetwork, AccountId>,
+	// Other locations, such as the users of sibling chains, get an account derived from the hash
+	// of the location.
+	Account32Hash<RelayNetwork, AccountId>,
 );
 
-/// Means for transacting assets on this chain.
+/// Means for transacting the native token KMD.
 pub type LocalAssetTransactor = CurrencyAdapter<
 	// Use this currency:
 	Balances,
 	// Use this currency when it is a fungible asset matching the given location or name:
-	IsConcrete<RelayLocation>,
+	IsConcrete<SelfReserve>,
 	// Do a simple punn to convert an AccountId32 MultiLocation into a native chain account ID:
 	LocationToAccountId,
 	// Our chain's account ID type (we can't get away without mentioning it explicitly):
 	AccountId,
-	// We don't track any teleports.
+	// KMD is never teleported, so no checking account is kept. Reserve transfers to other chains
+	// are accounted for in the sovereign accounts of those chains.
 	(),
 >;
 
+/// Converts between the location of an asset kept in `pallet_assets` and its asset id.
+pub type LocationToAssetId = (
+	// Foreign assets registered by governance, such as the relay-chain token and sibling-chain
+	// stablecoins:
+	AssetRegistry,
+	// Assets issued on this chain. Foreign asset ids are left to the registry, or the location
+	// of a foreign asset under the assets pallet would pass for a local asset:
+	ExceptForeignAssets<Runtime, AsPrefixedGeneralIndex<AssetsPalletLocation, AssetId, JustTry>>,
+);
+
+/// Means for transacting the assets kept in `pallet_assets`.
+pub type ForeignAssetsTransactor = FungiblesAdapter<
+	// Use this fungibles implementation:
+	Assets,
+	// Use this fungible when it is a concrete asset known to the converter:
+	ConvertedConcreteAssetId<AssetId, Balance, LocationToAssetId, JustTry>,
+	// Convert an XCM MultiLocation into a local account id:
+	LocationToAccountId,
+	// Our chain's account ID type (we can't get away without mentioning it explicitly):
+	AccountId,
+	// Assets are never teleported, so none are checked:
+	Nothing,
+	// The account teleported assets are checked out to:
+	CheckingAccount,
+>;
+
+/// Means for transacting assets on this chain.
+pub type AssetTransactors = (LocalAssetTransactor, ForeignAssetsTransactor);
+
 /// This is the type we use to convert an (incoming) XCM origin into a local `Origin` instance,
 /// ready for dispatching a transaction with Xcm's `Transact`. There is an `OriginKind` which can
 /// biases the kind of local `Origin` it will become.
@@ -76,9 +127,58 @@ pub type XcmOriginToTransactDispatchOrigin = (
 );
 
 parameter_types! {
-	// One XCM operation is 1_000_000_000 weight - almost certainly a conservative estimate.
-	pub UnitWeightCost: Weight = 1_000_000_000;
 	pub const MaxInstructions: u32 = 100;
+	/// The most assets a program may put into holding, which bounds the weight of wildcards.
+	pub const MaxAssetsIntoHolding: u32 = 64;
+	/// The price of one second of execution in KMD, which the relay-chain token is priced at
+	/// from genesis.
+	pub KmdPerSecond: u128 = WeightToFee::weight_to_fee(&WEIGHT_PER_SECOND);
+}
+
+/// Weighs XCM programs instruction by instruction, from the benchmarks of each instruction.
+pub type XcmWeigher = WeightInfoBounds<KumandraXcmWeight<Call>, Call, MaxInstructions>;
+
+/// Deposits execution fees paid in foreign assets into the treasury.
+///
+/// Deposits fail when the account cannot hold the asset, such as fees below the existential
+/// deposit of an asset the treasury holds none of. The fees then go to the block author, or else
+/// to the checking account, rather than being burned.
+pub struct ForeignFeesToTreasury;
+impl TakeRevenue for ForeignFeesToTreasury {
+	fn take_revenue(revenue: MultiAsset) {
+		for who in [Treasury::account_id(), Authorship::author(), CheckingAccount::get()] {
+			let location: MultiLocation =
+				Junction::AccountId32 { network: NetworkId::Any, id: who.into() }.into();
+			match ForeignAssetsTransactor::deposit_asset(&revenue, &location) {
+				Ok(()) => return,
+				Err(error) => log::warn!(
+					target: "xcm::fees",
+					"Failed to deposit fees {:?} into {:?}: {:?}",
+					revenue,
+					location,
+					error,
+				),
+			}
+		}
+	}
+}
+
+/// Accepts reserve transfers of assets from the chain they are native to, including assets
+/// issued by a pallet of that chain such as sibling-chain stablecoins.
+pub struct ReserveAssetsFromOrigin;
+impl FilterAssetLocation for ReserveAssetsFromOrigin {
+	fn filter_asset_location(asset: &MultiAsset, origin: &MultiLocation) -> bool {
+		let location = match &asset.id {
+			Concrete(location) => location,
+			Abstract(_) => return false,
+		};
+		let reserve = match (location.parents, location.first_interior()) {
+			(1, Some(Parachain(id))) => MultiLocation::new(1, X1(Parachain(*id))),
+			(1, _) => MultiLocation::parent(),
+			_ => return false,
+		};
+		&reserve == origin
+	}
 }
 
 match_types! {
@@ -86,6 +186,10 @@ match_types! {
 		MultiLocation { parents: 1, interior: Here } |
 		MultiLocation { parents: 1, interior: X1(Plurality { id: BodyId::Executive, .. }) }
 	};
+	pub type ParentOrSiblings: impl Contains<MultiLocation> = {
+		MultiLocation { parents: 1, interior: Here } |
+		MultiLocation { parents: 1, interior: X1(Parachain(_)) }
+	};
 }
 
 //TODO: move DenyThenTry to selendra's xcm module.
@@ -112,6 +216,38 @@ where
 	}
 }
 
+/// Allows paid execution of programs from `T` which first descend into an interior location of
+/// their origin, such as the account of a user on a sibling chain, and then pay for execution
+/// like top-level programs.
+pub struct AllowDescendedPaidExecutionFrom<T>(PhantomData<T>);
+impl<T: Contains<MultiLocation>> ShouldExecute for AllowDescendedPaidExecutionFrom<T> {
+	fn should_execute<Call>(
+		origin: &MultiLocation,
+		message: &mut Xcm<Call>,
+		max_weight: Weight,
+		weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		ensure!(T::contains(origin), ());
+		let mut descended = origin.clone();
+		match message.0.first() {
+			Some(DescendOrigin(interior)) =>
+				descended.append_with(interior.clone()).map_err(|_| ())?,
+			_ => return Err(()),
+		}
+
+		// The rest of the program must pay like a top-level program of the descended origin.
+		let descend = message.0.remove(0);
+		let result = AllowTopLevelPaidExecutionFrom::<Everything>::should_execute(
+			&descended,
+			message,
+			max_weight,
+			weight_credit,
+		);
+		message.0.insert(0, descend);
+		result
+	}
+}
+
 // See issue #5233
 pub struct DenyReserveTransferToRelayChain;
 impl ShouldExecute for DenyReserveTransferToRelayChain {
@@ -152,30 +288,75 @@ impl ShouldExecute for DenyReserveTransferToRelayChain {
 	}
 }
 
+/// Denies programs putting more than `MaxAssets` assets into holding, counting those of their
+/// error handlers and appendices, so that wildcards never match more assets than they are
+/// weighed for.
+pub struct DenyHoldingAbove<MaxAssets>(PhantomData<MaxAssets>);
+impl<MaxAssets: Get<u32>> ShouldExecute for DenyHoldingAbove<MaxAssets> {
+	fn should_execute<Call>(
+		_origin: &MultiLocation,
+		message: &mut Xcm<Call>,
+		_max_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		ensure!(assets_into_holding(message) <= MaxAssets::get() as usize, ());
+		Ok(())
+	}
+}
+
+/// The most assets `message` can put into holding.
+fn assets_into_holding<Call>(message: &Xcm<Call>) -> usize {
+	message
+		.0
+		.iter()
+		.map(|instruction| match instruction {
+			WithdrawAsset(assets) |
+			ReserveAssetDeposited(assets) |
+			ReceiveTeleportedAsset(assets) |
+			ClaimAsset { assets, .. } => assets.len(),
+			SetErrorHandler(xcm) | SetAppendix(xcm) => assets_into_holding(xcm),
+			_ => 0,
+		})
+		.fold(0, usize::saturating_add)
+}
+
 pub type Barrier = DenyThenTry<
 	DenyReserveTransferToRelayChain,
-	(
-		TakeWeightCredit,
-		AllowTopLevelPaidExecutionFrom<Everything>,
-		AllowUnpaidExecutionFrom<ParentOrParentsExecutivePlurality>,
-		// ^^^ Parent and its exec plurality get free execution
-	),
+	DenyThenTry<DenyHoldingAbove<MaxAssetsIntoHolding>, AllowedExecution>,
 >;
 
+/// The programs allowed to execute, unless denied by the [`Barrier`].
+pub type AllowedExecution = (
+	TakeWeightCredit,
+	AllowTopLevelPaidExecutionFrom<Everything>,
+	// Users of the parent and of siblings pay for their own execution.
+	AllowDescendedPaidExecutionFrom<ParentOrSiblings>,
+	AllowUnpaidExecutionFrom<ParentOrParentsExecutivePlurality>,
+	// ^^^ Parent and its exec plurality get free execution
+	// Responses to our queries, such as version notifications.
+	AllowKnownQueryResponses<SelendraXcm>,
+	// Subscriptions to our XCM version.
+	AllowSubscriptionsFrom<ParentOrSiblings>,
+);
+
 pub struct XcmConfig;
 impl xcm_executor::Config for XcmConfig {
 	type Call = Call;
 	type XcmSender = XcmRouter;
 	// How to withdraw and deposit an asset.
-	type AssetTransactor = LocalAssetTransactor;
+	type AssetTransactor = AssetTransactors;
 	type OriginConverter = XcmOriginToTransactDispatchOrigin;
-	type IsReserve = NativeAsset;
+	type IsReserve = ReserveAssetsFromOrigin;
 	type IsTeleporter = (); // Teleporting is disabled.
 	type LocationInverter = LocationInverter<Ancestry>;
 	type Barrier = Barrier;
-	type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
-	type Trader =
-		UsingComponents<WeightToFee, RelayLocation, AccountId, Balances, ToAuthor<Runtime>>;
+	type Weigher = XcmWeigher;
+	type Trader = (
+		UsingComponents<WeightToFee, SelfReserve, AccountId, Balances, ToAuthor<Runtime>>,
+		// Any foreign asset priced in the registry, such as the relay-chain token or sibling-chain
+		// stablecoins, can pay for execution too.
+		UsingRegisteredAssets<Runtime, ForeignFeesToTreasury>,
+	);
 	type ResponseHandler = SelendraXcm;
 	type AssetTrap = SelendraXcm;
 	type AssetClaims = SelendraXcm;
@@ -204,8 +385,9 @@ impl pallet_xcm::Config for Runtime {
 	// Needs to be `Everything` for local testing.
 	type XcmExecutor = XcmExecutor<XcmConfig>;
 	type XcmTeleportFilter = Everything;
-	type XcmReserveTransferFilter = Nothing;
-	type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+	// Reserve transfers are limited to the destinations allowed by governance.
+	type XcmReserveTransferFilter = XcmTransfer;
+	type Weigher = XcmWeigher;
 	type LocationInverter = LocationInverter<Ancestry>;
 	type Origin = Origin;
 	type Call = Call;
@@ -215,6 +397,27 @@ impl pallet_xcm::Config for Runtime {
 	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
 }
 
+/// Converts an account into its location on this chain.
+pub struct AccountIdToMultiLocation;
+impl Convert<AccountId, MultiLocation> for AccountIdToMultiLocation {
+	fn convert(account: AccountId) -> MultiLocation {
+		Junction::AccountId32 { network: NetworkId::Any, id: account.into() }.into()
+	}
+}
+
+impl pallet_xcm_transfer::Config for Runtime {
+	type Event = Event;
+	type AccountIdToMultiLocation = AccountIdToMultiLocation;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type Weigher = XcmWeigher;
+	type LocationInverter = LocationInverter<Ancestry>;
+	type DestinationOrigin = EnsureRootOrXcmAdmin;
+}
+
+impl pallet_xcm_versions::Config for Runtime {
+	type AdminOrigin = EnsureRootOrXcmAdmin;
+}
+
 impl forests_pallet_xcm::Config for Runtime {
 	type Event = Event;
 	type XcmExecutor = XcmExecutor<XcmConfig>;